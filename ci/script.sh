@@ -8,6 +8,13 @@ main() {
         export RUSTFLAGS=--cfg=travis
     fi
 
+    # libc releases after 0.2.151 need a newer compiler than our minimum
+    # supported Rust version, so pin the oldest libc nix supports there.
+    case "$TRAVIS_RUST_VERSION" in
+        beta|nightly|stable) ;;
+        *) cargo update -p libc --precise 0.2.151 ;;
+    esac
+
     # Build debug and release targets
     cross build --target $TARGET
     cross build --target $TARGET --release
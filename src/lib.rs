@@ -17,6 +17,12 @@ extern crate bitflags;
 #[macro_use]
 extern crate cfg_if;
 extern crate void;
+#[cfg(feature = "rand")]
+extern crate rand;
+#[cfg(feature = "smoltcp")]
+extern crate smoltcp;
+#[cfg(feature = "ipnet")]
+extern crate ipnet;
 
 #[cfg(test)]
 extern crate nix_test as nixtest;
@@ -1,18 +1,36 @@
 use super::{consts, sa_family_t};
 use {Errno, Error, Result, NixPath};
 use libc;
-use std::{fmt, hash, mem, net, ptr};
+use std::{cmp, fmt, fs, hash, io, mem, net, ptr, slice};
+use std::net::ToSocketAddrs;
+use std::str::FromStr;
 use std::ffi::OsStr;
 use std::path::Path;
 use std::os::unix::ffi::OsStrExt;
+#[cfg(feature = "rand")]
+use rand::Rng;
+#[cfg(feature = "test-util")]
+use ::sys::socket::test_util::TempSocketDir;
 #[cfg(any(target_os = "linux", target_os = "android"))]
 use ::sys::socket::addr::netlink::NetlinkAddr;
-#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use ::sys::socket::addr::key::KeyAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use ::sys::socket::addr::vsock::VsockAddr;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+use ::sys::socket::addr::xdp::XdpAddr;
+#[cfg(any(target_os = "macos", target_os = "ios",
+          all(target_os = "linux", target_env = "gnu")))]
 use std::os::unix::io::RawFd;
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 use ::sys::socket::addr::sys_control::SysControlAddr;
-
-// TODO: uncomment out IpAddr functions: rust-lang/rfcs#988
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "openbsd",
+          target_os = "netbsd"))]
+use ::sys::socket::addr::link::LinkAddr;
 
 /*
  *
@@ -29,9 +47,148 @@ pub enum AddressFamily {
     #[cfg(any(target_os = "linux", target_os = "android"))]
     Netlink = consts::AF_NETLINK,
     #[cfg(any(target_os = "linux", target_os = "android"))]
+    Key = consts::AF_KEY,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Vsock = consts::AF_VSOCK,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
     Packet = consts::AF_PACKET,
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    Xdp = consts::AF_XDP,
     #[cfg(any(target_os = "macos", target_os = "ios"))]
     System = consts::AF_SYSTEM,
+    #[cfg(any(target_os = "macos",
+              target_os = "ios",
+              target_os = "freebsd",
+              target_os = "dragonfly",
+              target_os = "openbsd",
+              target_os = "netbsd"))]
+    Link = consts::AF_LINK,
+}
+
+impl AddressFamily {
+    /// Return the name of the C constant for this family, like `"AF_INET"`
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            AddressFamily::Unix => "AF_UNIX",
+            AddressFamily::Inet => "AF_INET",
+            AddressFamily::Inet6 => "AF_INET6",
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Netlink => "AF_NETLINK",
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Key => "AF_KEY",
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Vsock => "AF_VSOCK",
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Packet => "AF_PACKET",
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            AddressFamily::Xdp => "AF_XDP",
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            AddressFamily::System => "AF_SYSTEM",
+            #[cfg(any(target_os = "macos",
+              target_os = "ios",
+              target_os = "freebsd",
+              target_os = "dragonfly",
+              target_os = "openbsd",
+              target_os = "netbsd"))]
+            AddressFamily::Link => "AF_LINK",
+        }
+    }
+
+    /// Return the family for the raw `AF_*` value `family`, e.g. as found in
+    /// `sa_family`, or `None` if it isn't one of the variants of this enum.
+    pub fn from_i32(family: i32) -> Option<AddressFamily> {
+        match family {
+            consts::AF_UNIX => Some(AddressFamily::Unix),
+            consts::AF_INET => Some(AddressFamily::Inet),
+            consts::AF_INET6 => Some(AddressFamily::Inet6),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            consts::AF_NETLINK => Some(AddressFamily::Netlink),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            consts::AF_KEY => Some(AddressFamily::Key),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            consts::AF_VSOCK => Some(AddressFamily::Vsock),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            consts::AF_PACKET => Some(AddressFamily::Packet),
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            consts::AF_XDP => Some(AddressFamily::Xdp),
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            consts::AF_SYSTEM => Some(AddressFamily::System),
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            consts::AF_LINK => Some(AddressFamily::Link),
+            _ => None,
+        }
+    }
+
+    /// Return the raw `AF_*` value of this family; the inverse of `from_i32`
+    pub fn to_i32(&self) -> i32 {
+        *self as i32
+    }
+
+    /// Return the name and numeric value of this family, like
+    /// `"AF_INET (2)"`, for use in diagnostics.
+    pub fn describe(&self) -> String {
+        format!("{} ({})", self.as_str(), *self as i32)
+    }
+
+    /// Return the `PF_*` protocol family constant corresponding to this
+    /// address family.  All supported platforms define the two to be equal,
+    /// but code that passes a protocol family to `socket` can use this to say
+    /// so explicitly.
+    pub fn to_pf(&self) -> i32 {
+        match *self {
+            AddressFamily::Unix => libc::PF_UNIX,
+            AddressFamily::Inet => libc::PF_INET,
+            AddressFamily::Inet6 => libc::PF_INET6,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Netlink => libc::PF_NETLINK,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Key => libc::PF_KEY,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Vsock => libc::PF_VSOCK,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Packet => libc::PF_PACKET,
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            AddressFamily::Xdp => libc::PF_XDP,
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            AddressFamily::System => libc::PF_SYSTEM,
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            AddressFamily::Link => libc::PF_LINK,
+        }
+    }
+
+    /// Return the `IPPROTO_*` value to pass to `socket` when opening a raw
+    /// socket of this family for `transport`, or `None` if this is not an
+    /// internet family.
+    pub fn raw_protocol_for(&self, transport: Transport) -> Option<i32> {
+        match (*self, transport) {
+            (AddressFamily::Inet, Transport::Tcp) |
+            (AddressFamily::Inet6, Transport::Tcp) => Some(consts::IPPROTO_TCP),
+            (AddressFamily::Inet, Transport::Udp) |
+            (AddressFamily::Inet6, Transport::Udp) => Some(consts::IPPROTO_UDP),
+            (AddressFamily::Inet, Transport::Icmp) => Some(libc::IPPROTO_ICMP),
+            (AddressFamily::Inet6, Transport::Icmp) => Some(libc::IPPROTO_ICMPV6),
+            _ => None,
+        }
+    }
+}
+
+/// Transport protocols, for use with `AddressFamily::raw_protocol_for`
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum Transport {
+    Tcp,
+    Udp,
+    /// ICMP for `Inet`, ICMPv6 for `Inet6`
+    Icmp,
 }
 
 #[derive(Copy)]
@@ -85,6 +242,10 @@ impl InetAddr {
         }
     }
     /// Gets the IP address associated with this socket address.
+    ///
+    /// An IPv6 scope id is a property of the socket address, not of the IP,
+    /// so it is not part of the result.  Use `to_std`, `v6_parts` or
+    /// `SockAddr::ip_and_scope` to keep it.
     pub fn ip(&self) -> IpAddr {
         match *self {
             InetAddr::V4(ref sa) => IpAddr::V4(Ipv4Addr(sa.sin_addr)),
@@ -92,6 +253,17 @@ impl InetAddr {
         }
     }
 
+    /// For an IPv6 address, return the IP together with the flow info and
+    /// scope id, as `(ip, flowinfo, scope_id)`.  `None` for IPv4.
+    pub fn v6_parts(&self) -> Option<(Ipv6Addr, u32, u32)> {
+        match *self {
+            InetAddr::V4(_) => None,
+            InetAddr::V6(ref sa) => {
+                Some((Ipv6Addr(sa.sin6_addr), sa.sin6_flowinfo, sa.sin6_scope_id))
+            }
+        }
+    }
+
     /// Gets the port number associated with this socket address
     pub fn port(&self) -> u16 {
         match *self {
@@ -100,6 +272,143 @@ impl InetAddr {
         }
     }
 
+    /// Return the wildcard address of the same family as this one, i.e.
+    /// `0.0.0.0:port` or `[::]:port`.
+    pub fn any_like(&self, port: u16) -> InetAddr {
+        match *self {
+            InetAddr::V4(_) => InetAddr::new(IpAddr::V4(Ipv4Addr::any()), port),
+            InetAddr::V6(_) => InetAddr::new(IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0)), port),
+        }
+    }
+
+    /// Return the address to bind a socket to for receiving multicast on
+    /// `group_port`: the IPv6 wildcard address if `v6` is true, the IPv4 one
+    /// otherwise.
+    pub fn multicast_listener(group_port: u16, v6: bool) -> InetAddr {
+        if v6 {
+            InetAddr::new(IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0)), group_port)
+        } else {
+            InetAddr::new(IpAddr::V4(Ipv4Addr::any()), group_port)
+        }
+    }
+
+    /// Return the IP as 16 bytes in network byte order, with IPv4 addresses
+    /// converted to their IPv4-mapped form (`::ffff:a.b.c.d`).  This gives
+    /// dual-stack tables a single fixed-width key.
+    pub fn to_mapped_bytes(&self) -> [u8; 16] {
+        match *self {
+            InetAddr::V4(ref sa) => {
+                let o = Ipv4Addr(sa.sin_addr).octets();
+                [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, o[0], o[1], o[2], o[3]]
+            }
+            InetAddr::V6(ref sa) => sa.sin6_addr.s6_addr,
+        }
+    }
+
+    /// Return the IPv4-mapped IPv6 form (`[::ffff:a.b.c.d]:port`) of an IPv4
+    /// address, for use with a dual-stack IPv6 socket.  IPv6 addresses are
+    /// returned unchanged.
+    pub fn to_dualstack(&self) -> InetAddr {
+        match *self {
+            InetAddr::V4(_) => {
+                let ip = Ipv6Addr::from_std(&net::Ipv6Addr::from(self.to_mapped_bytes()));
+                InetAddr::new(IpAddr::V6(ip), self.port())
+            }
+            InetAddr::V6(sa) => InetAddr::V6(sa),
+        }
+    }
+
+    /// Encode this address as a tag byte (`4` or `6`), followed by the IP and
+    /// then the port, both in network byte order.  The IPv6 flow label and
+    /// scope id are not encoded.
+    pub fn encode_tagged(&self) -> Vec<u8> {
+        let mut ret = Vec::with_capacity(19);
+        match *self {
+            InetAddr::V4(ref sa) => {
+                ret.push(4);
+                ret.extend_from_slice(&Ipv4Addr(sa.sin_addr).octets());
+            }
+            InetAddr::V6(ref sa) => {
+                ret.push(6);
+                ret.extend_from_slice(&sa.sin6_addr.s6_addr);
+            }
+        }
+        ret.extend_from_slice(&be_u16(self.port()));
+        ret
+    }
+
+    /// Decode the form produced by `encode_tagged`.  Fails with `EINVAL` if
+    /// the tag is unknown or the length doesn't match it.
+    pub fn decode_tagged(bytes: &[u8]) -> Result<InetAddr> {
+        let (ip, rest) = match bytes.split_first() {
+            Some((&4, rest)) if rest.len() == 6 => {
+                (IpAddr::V4(try!(Ipv4Addr::from_be_bytes_slice(&rest[..4]))), &rest[4..])
+            }
+            Some((&6, rest)) if rest.len() == 18 => {
+                (IpAddr::V6(try!(Ipv6Addr::from_be_bytes_slice(&rest[..16]))), &rest[16..])
+            }
+            _ => return Err(Error::invalid_argument()),
+        };
+        let port = ((rest[0] as u16) << 8) | rest[1] as u16;
+        Ok(InetAddr::new(ip, port))
+    }
+
+    /// Return this address, or fail with `EINVAL` if it is an IPv4-mapped
+    /// IPv6 address (`::ffff:a.b.c.d`).
+    ///
+    /// Security-sensitive callers can use this to make sure that an address
+    /// meant for an IPv6-only socket doesn't silently refer to an IPv4 host.
+    pub fn reject_mapped(self) -> Result<InetAddr> {
+        match self {
+            InetAddr::V6(ref sa) if is_v4_mapped(&sa.sin6_addr.s6_addr) => {
+                Err(Error::invalid_argument())
+            }
+            _ => Ok(self),
+        }
+    }
+
+    /// Hash this address like the `Hash` impl does, but leave out the IPv6
+    /// scope id.
+    ///
+    /// This is consistent with comparing addresses by
+    /// `a.without_scope() == b.without_scope()`, not with `==`; maps that use
+    /// this hash must use that comparison as well.
+    pub fn hash_ignoring_scope<H: hash::Hasher>(&self, s: &mut H) {
+        match *self {
+            InetAddr::V4(_) => hash::Hash::hash(self, s),
+            InetAddr::V6(ref a) => {
+                hash::Hash::hash(&( a.sin6_family,
+                                    a.sin6_port,
+                                    &a.sin6_addr.s6_addr,
+                                    a.sin6_flowinfo ), s)
+            }
+        }
+    }
+
+    /// Return a copy of this address with the port changed to `port`
+    pub fn with_port(&self, port: u16) -> InetAddr {
+        match *self {
+            InetAddr::V4(mut sa) => {
+                sa.sin_port = port.to_be();
+                InetAddr::V4(sa)
+            }
+            InetAddr::V6(mut sa) => {
+                sa.sin6_port = port.to_be();
+                InetAddr::V6(sa)
+            }
+        }
+    }
+
+    /// Return a copy of this address with the port clamped into
+    /// `min...max`: a port below `min` becomes `min`, one above `max` becomes
+    /// `max`.  Fails with `EINVAL` if `min > max`.
+    pub fn with_clamped_port(&self, min: u16, max: u16) -> Result<InetAddr> {
+        if min > max {
+            return Err(Error::invalid_argument());
+        }
+        Ok(self.with_port(cmp::min(cmp::max(self.port(), min), max)))
+    }
+
     pub fn to_std(&self) -> net::SocketAddr {
         match *self {
             InetAddr::V4(ref sa) => net::SocketAddr::V4(
@@ -118,6 +427,126 @@ impl InetAddr {
     pub fn to_str(&self) -> String {
         format!("{}", self)
     }
+
+    /// Return the authority component for a URL that refers to this address:
+    /// `ip:port` for IPv4 and `[ip]:port` for IPv6.  The IPv6 brackets are
+    /// always present.  Scope ids are not included.
+    pub fn to_authority(&self) -> String {
+        match *self {
+            InetAddr::V4(_) => format!("{}:{}", self.ip(), self.port()),
+            InetAddr::V6(_) => format!("[{}]:{}", self.ip(), self.port()),
+        }
+    }
+
+    /// Return the canonical `ip:port` or `[ip]:port` form of this address,
+    /// as written by `Display`: IPv6 addresses in lowercase, compressed
+    /// notation and followed by `%scope` if they have a scope id.  Equal
+    /// addresses yield equal strings however they were originally written,
+    /// which makes this suitable as a map key.
+    pub fn canonical_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// Render this address like `Display` does, as `host:port` or
+    /// `[host]:port`, but with the host part produced by `render`, e.g. to
+    /// substitute a reverse-resolved name.  IPv6 addresses are bracketed
+    /// whatever `render` returns.
+    pub fn format_with<F: Fn(&IpAddr) -> String>(&self, render: F) -> String {
+        let host = render(&self.ip());
+        match *self {
+            InetAddr::V4(_) => format!("{}:{}", host, self.port()),
+            InetAddr::V6(_) => format!("[{}]:{}", host, self.port()),
+        }
+    }
+
+    /// Whether the IPs of both addresses lie in the same `/prefix_len`
+    /// network, e.g. to decide whether `other` is reachable on-link.  A
+    /// `prefix_len` beyond the address width saturates.  Ports and scope ids
+    /// are ignored, and addresses of different families are never on the
+    /// same link.
+    pub fn same_link(&self, other: &InetAddr, prefix_len: u8) -> bool {
+        match (*self, *other) {
+            (InetAddr::V4(ref a), InetAddr::V4(ref b)) => {
+                let mask = v4_mask(cmp::min(prefix_len, 32));
+                u32::from_be(a.sin_addr.s_addr) & mask == u32::from_be(b.sin_addr.s_addr) & mask
+            }
+            (InetAddr::V6(ref a), InetAddr::V6(ref b)) => {
+                Ipv6Addr(a.sin6_addr).network(prefix_len) ==
+                    Ipv6Addr(b.sin6_addr).network(prefix_len)
+            }
+            _ => false,
+        }
+    }
+
+    /// Return whether this address's IP lies within any of `cidrs`.
+    pub fn in_any_cidr(&self, cidrs: &[Cidr]) -> bool {
+        let ip = self.ip();
+        cidrs.iter().any(|cidr| cidr.contains(&ip))
+    }
+
+    /// Return a copy of this address with the IPv6 scope id cleared.
+    ///
+    /// Useful when addresses learned on different interfaces should compare
+    /// (and hash) equal.  IPv4 addresses are returned unchanged.
+    pub fn without_scope(&self) -> InetAddr {
+        match *self {
+            InetAddr::V4(sa) => InetAddr::V4(sa),
+            InetAddr::V6(mut sa) => {
+                sa.sin6_scope_id = 0;
+                InetAddr::V6(sa)
+            }
+        }
+    }
+
+    /// Whether this is an IPv6 unspecified (`::`) or loopback (`::1`) address
+    /// with a nonzero scope id or flow label, e.g. `::%2`.
+    ///
+    /// Such combinations are legal but almost always a configuration bug;
+    /// this is meant as a lint, not as a validity check.
+    pub fn is_suspicious(&self) -> bool {
+        match *self {
+            InetAddr::V4(_) => false,
+            InetAddr::V6(ref sa) => {
+                let bytes = &sa.sin6_addr.s6_addr;
+                let special = bytes[..15].iter().all(|&b| b == 0) && bytes[15] <= 1;
+                special && (sa.sin6_scope_id != 0 || sa.sin6_flowinfo != 0)
+            }
+        }
+    }
+
+    /// Whether both addresses refer to the same IP address, ignoring the
+    /// port and the IPv6 flow label.  IPv6 addresses with different scope
+    /// ids are considered different, and a v4 address never equals a v6 one.
+    pub fn ip_eq(&self, other: &InetAddr) -> bool {
+        match (*self, *other) {
+            (InetAddr::V4(ref a), InetAddr::V4(ref b)) => {
+                a.sin_addr.s_addr == b.sin_addr.s_addr
+            }
+            (InetAddr::V6(ref a), InetAddr::V6(ref b)) => {
+                a.sin6_addr.s6_addr == b.sin6_addr.s6_addr &&
+                    a.sin6_scope_id == b.sin6_scope_id
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the IP is the wildcard address, `0.0.0.0` or `::`, whatever
+    /// the port
+    pub fn is_wildcard(&self) -> bool {
+        match *self {
+            InetAddr::V4(ref sa) => sa.sin_addr.s_addr == 0,
+            InetAddr::V6(ref sa) => sa.sin6_addr.s6_addr == [0; 16],
+        }
+    }
+
+    /// Like `SockAddr::as_ffi_pair`, for callers that already know they have
+    /// an internet address.
+    pub unsafe fn as_ffi_pair(&self) -> (&libc::sockaddr, libc::socklen_t) {
+        match *self {
+            InetAddr::V4(ref addr) => (mem::transmute(addr), mem::size_of::<libc::sockaddr_in>() as libc::socklen_t),
+            InetAddr::V6(ref addr) => (mem::transmute(addr), mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t),
+        }
+    }
 }
 
 impl PartialEq for InetAddr {
@@ -166,15 +595,77 @@ impl Clone for InetAddr {
     }
 }
 
+/// The alternate form (`{:#}`) is tagged with an `inet:` prefix and can be
+/// parsed back with `SockAddr::from_tagged_string`.
 impl fmt::Display for InetAddr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            try!(f.write_str("inet:"));
+        }
         match *self {
             InetAddr::V4(_) => write!(f, "{}:{}", self.ip(), self.port()),
+            InetAddr::V6(ref sa) if sa.sin6_scope_id != 0 => {
+                write!(f, "[{}%{}]:{}", self.ip(), sa.sin6_scope_id, self.port())
+            }
             InetAddr::V6(_) => write!(f, "[{}]:{}", self.ip(), self.port()),
         }
     }
 }
 
+impl FromStr for InetAddr {
+    type Err = Error;
+
+    /// Parse `ip:port` or `[ip]:port`, as produced by `Display`.  An IPv6
+    /// address may carry a numeric scope id, as in `[fe80::1%2]:80`, which
+    /// is also how `Display` writes a nonzero scope id.  Fails
+    /// with `EINVAL` for anything else, including an IP without a port.
+    fn from_str(s: &str) -> Result<InetAddr> {
+        let (s, scope_id) = match (s.find('%'), s.find(']')) {
+            (Some(pct), Some(close)) if s.starts_with('[') && pct < close => {
+                match s[pct + 1..close].parse::<u32>() {
+                    Ok(id) => (format!("{}{}", &s[..pct], &s[close..]), Some(id)),
+                    Err(_) => return Err(Error::invalid_argument()),
+                }
+            }
+            _ => (s.to_owned(), None),
+        };
+        let mut inet = match s.parse::<net::SocketAddr>() {
+            Ok(std) => InetAddr::from_std(&std),
+            Err(_) => return Err(Error::invalid_argument()),
+        };
+        if let (Some(id), &mut InetAddr::V6(ref mut sa)) = (scope_id, &mut inet) {
+            sa.sin6_scope_id = id;
+        }
+        Ok(inet)
+    }
+}
+
+/// Hash the directional 4-tuple `(src ip, src port, dst ip, dst port)` for
+/// use as a flow table key.
+///
+/// The hash is FNV-1a over the IP version, address bytes and port (in
+/// network byte order) of each endpoint, so it is stable across restarts
+/// and platforms.  Swapping `src` and `dst` yields a different hash, and
+/// IPv4 and IPv6 endpoints never collide by construction of the input.
+/// Scope id and flow info are ignored.
+pub fn flow_hash(src: &InetAddr, dst: &InetAddr) -> u64 {
+    let mut h = Fnv1a::new();
+    for addr in &[src, dst] {
+        match **addr {
+            InetAddr::V4(ref sa) => {
+                h.write(&[4]);
+                h.write(&Ipv4Addr(sa.sin_addr).octets());
+            }
+            InetAddr::V6(ref sa) => {
+                h.write(&[6]);
+                h.write(&sa.sin6_addr.s6_addr);
+            }
+        }
+        h.write(&be_u16(addr.port()));
+    }
+    h.finish()
+}
+
 /*
  *
  * ===== IpAddr =====
@@ -201,7 +692,6 @@ impl IpAddr {
         IpAddr::V6(Ipv6Addr::new(a, b, c, d, e, f, g, h))
     }
 
-    /*
     pub fn from_std(std: &net::IpAddr) -> IpAddr {
         match *std {
             net::IpAddr::V4(ref std) => IpAddr::V4(Ipv4Addr::from_std(std)),
@@ -215,7 +705,18 @@ impl IpAddr {
             IpAddr::V6(ref ip) => net::IpAddr::V6(ip.to_std()),
         }
     }
-    */
+}
+
+impl From<net::IpAddr> for IpAddr {
+    fn from(std: net::IpAddr) -> IpAddr {
+        IpAddr::from_std(&std)
+    }
+}
+
+impl From<IpAddr> for net::IpAddr {
+    fn from(ip: IpAddr) -> net::IpAddr {
+        ip.to_std()
+    }
 }
 
 impl fmt::Display for IpAddr {
@@ -251,6 +752,13 @@ impl Ipv4Addr {
         Ipv4Addr::new(bits[0], bits[1], bits[2], bits[3])
     }
 
+    /// Generate an address uniformly at random from `rng`.  With a seeded
+    /// `rng` the sequence of addresses is reproducible.
+    #[cfg(feature = "rand")]
+    pub fn from_rng<R: Rng + ?Sized>(rng: &mut R) -> Ipv4Addr {
+        Ipv4Addr(libc::in_addr { s_addr: rng.next_u32() })
+    }
+
     pub fn any() -> Ipv4Addr {
         Ipv4Addr(libc::in_addr { s_addr: consts::INADDR_ANY })
     }
@@ -264,6 +772,139 @@ impl Ipv4Addr {
         let bits = self.octets();
         net::Ipv4Addr::new(bits[0], bits[1], bits[2], bits[3])
     }
+
+    /// Whether this is a loopback address (`127.0.0.0/8`)
+    pub fn is_loopback(&self) -> bool {
+        self.octets()[0] == 127
+    }
+
+    /// Whether this is the unspecified address `0.0.0.0`
+    pub fn is_unspecified(&self) -> bool {
+        self.octets() == [0, 0, 0, 0]
+    }
+
+    /// Whether this is a multicast address (`224.0.0.0/4`)
+    pub fn is_multicast(&self) -> bool {
+        self.octets()[0] & 0xf0 == 224
+    }
+
+    /// Return the address as a `Vec` of 4 bytes in network (big-endian)
+    /// order, e.g. for a protobuf `bytes` field.
+    pub fn to_be_bytes_vec(&self) -> Vec<u8> {
+        self.octets().to_vec()
+    }
+
+    /// Create an address from 4 bytes in network (big-endian) order.  Fails
+    /// with `EINVAL` if `bytes` has any other length.
+    pub fn from_be_bytes_slice(bytes: &[u8]) -> Result<Ipv4Addr> {
+        if bytes.len() != 4 {
+            return Err(Error::invalid_argument());
+        }
+        Ok(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]))
+    }
+
+    /// Return the number of leading bits this address has in common with
+    /// `other`, from 0 to 32.
+    pub fn common_prefix_len(&self, other: &Ipv4Addr) -> u8 {
+        (u32::from_be(self.0.s_addr) ^ u32::from_be(other.0.s_addr)).leading_zeros() as u8
+    }
+
+    /// Return the `/32` CIDR block that contains just this address
+    pub fn to_host_cidr(&self) -> CidrV4 {
+        CidrV4 { addr: *self, prefix_len: 32 }
+    }
+
+    /// Whether this address equals `bits`, given in host byte order, e.g.
+    /// `0x7f000001` for `127.0.0.1`
+    pub fn eq_bits(&self, bits: u32) -> bool {
+        u32::from_be(self.0.s_addr) == bits
+    }
+
+    /// Return the labels of the reverse DNS (PTR) query name, e.g.
+    /// `["4", "3", "2", "1", "in-addr", "arpa"]` for `1.2.3.4`
+    pub fn reverse_dns_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self.octets().iter().rev()
+            .map(|o| o.to_string())
+            .collect();
+        labels.push("in-addr".to_owned());
+        labels.push("arpa".to_owned());
+        labels
+    }
+
+    /// Return the address as 8 lowercase hex digits without separators, e.g.
+    /// `"7f000001"` for `127.0.0.1`
+    pub fn to_hex_string(&self) -> String {
+        self.octets().iter().map(|o| format!("{:02x}", o)).collect()
+    }
+
+    /// Parse the form produced by `to_hex_string`; upper case digits are
+    /// accepted too.  Fails with `EINVAL` unless `s` is exactly 8 hex digits.
+    pub fn from_hex_string(s: &str) -> Result<Ipv4Addr> {
+        if s.len() != 8 || !s.bytes().all(|b| (b as char).is_digit(16)) {
+            return Err(Error::invalid_argument());
+        }
+        let mut octets = [0u8; 4];
+        for (i, o) in octets.iter_mut().enumerate() {
+            *o = try!(u8::from_str_radix(&s[2 * i..2 * i + 2], 16)
+                      .map_err(|_| Error::invalid_argument()));
+        }
+        Ok(Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]))
+    }
+
+    /// Whether `other` immediately precedes or follows this address.  There
+    /// is no wraparound: `255.255.255.255` and `0.0.0.0` are not adjacent.
+    pub fn is_adjacent(&self, other: &Ipv4Addr) -> bool {
+        let (a, b) = (u32::from_be(self.0.s_addr), u32::from_be(other.0.s_addr));
+        a.checked_add(1) == Some(b) || b.checked_add(1) == Some(a)
+    }
+
+    /// Whether this is the network address of its `/prefix_len` subnet, i.e.
+    /// all host bits are zero.  Always false for prefixes of 31 bits or more,
+    /// whose addresses are all usable by hosts (RFC 3021).
+    pub fn is_network_address(&self, prefix_len: u8) -> bool {
+        prefix_len < 31 && u32::from_be(self.0.s_addr) & !v4_mask(prefix_len) == 0
+    }
+
+    /// Whether this is the broadcast address of its `/prefix_len` subnet,
+    /// i.e. all host bits are one.  Always false for prefixes of 31 bits or
+    /// more, whose addresses are all usable by hosts (RFC 3021).
+    pub fn is_broadcast_address(&self, prefix_len: u8) -> bool {
+        let host = !v4_mask(cmp::min(prefix_len, 32));
+        prefix_len < 31 && u32::from_be(self.0.s_addr) & host == host
+    }
+
+    /// Return the netmask for a prefix of `prefix_len` bits, e.g.
+    /// `255.255.255.0` for 24.  A `prefix_len` above 32 saturates, giving
+    /// `255.255.255.255`.
+    pub fn netmask(prefix_len: u8) -> Ipv4Addr {
+        Ipv4Addr(libc::in_addr { s_addr: v4_mask(cmp::min(prefix_len, 32)).to_be() })
+    }
+
+    /// Return the default netmask of this address's class in the obsolete
+    /// classful scheme: `255.0.0.0` for class A (`0.0.0.0/1`), `255.255.0.0`
+    /// for class B (`128.0.0.0/2`) and `255.255.255.0` for class C
+    /// (`192.0.0.0/3`).  Class D (multicast) and E addresses have no default
+    /// netmask.
+    pub fn classful_netmask(&self) -> Option<Ipv4Addr> {
+        match self.octets()[0] {
+            0...127 => Some(Ipv4Addr::netmask(8)),
+            128...191 => Some(Ipv4Addr::netmask(16)),
+            192...223 => Some(Ipv4Addr::netmask(24)),
+            _ => None,
+        }
+    }
+}
+
+impl From<net::Ipv4Addr> for Ipv4Addr {
+    fn from(std: net::Ipv4Addr) -> Ipv4Addr {
+        Ipv4Addr::from_std(&std)
+    }
+}
+
+impl From<Ipv4Addr> for net::Ipv4Addr {
+    fn from(addr: Ipv4Addr) -> net::Ipv4Addr {
+        addr.to_std()
+    }
 }
 
 impl PartialEq for Ipv4Addr {
@@ -275,6 +916,18 @@ impl PartialEq for Ipv4Addr {
 impl Eq for Ipv4Addr {
 }
 
+impl PartialEq<[u8; 4]> for Ipv4Addr {
+    fn eq(&self, other: &[u8; 4]) -> bool {
+        self.octets() == *other
+    }
+}
+
+impl PartialEq<Ipv4Addr> for [u8; 4] {
+    fn eq(&self, other: &Ipv4Addr) -> bool {
+        *self == other.octets()
+    }
+}
+
 impl hash::Hash for Ipv4Addr {
     fn hash<H: hash::Hasher>(&self, s: &mut H) {
         self.0.s_addr.hash(s)
@@ -294,6 +947,37 @@ impl fmt::Display for Ipv4Addr {
     }
 }
 
+impl FromStr for Ipv4Addr {
+    type Err = Error;
+
+    /// Parse an address in dotted-quad notation.  Fails with `EINVAL` if `s`
+    /// isn't one; in particular, only ASCII digits are accepted, never
+    /// Unicode lookalikes.
+    fn from_str(s: &str) -> Result<Ipv4Addr> {
+        if !s.bytes().all(|b| b < 0x80) {
+            return Err(Error::invalid_argument());
+        }
+        match s.parse::<net::Ipv4Addr>() {
+            Ok(std) => Ok(Ipv4Addr::from_std(&std)),
+            Err(_) => Err(Error::invalid_argument()),
+        }
+    }
+}
+
+/// Formats the address as 32 binary digits.  The alternate form (`{:#b}`)
+/// separates the octets with dots.
+impl fmt::Binary for Ipv4Addr {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for (i, octet) in self.octets().iter().enumerate() {
+            if i > 0 && fmt.alternate() {
+                try!(fmt.write_str("."));
+            }
+            try!(write!(fmt, "{:08b}", octet));
+        }
+        Ok(())
+    }
+}
+
 /*
  *
  * ===== Ipv6Addr =====
@@ -313,12 +997,6 @@ macro_rules! to_u8_array {
     }
 }
 
-macro_rules! to_u16_array {
-    ($slf:ident, $($first:expr, $second:expr),*) => {
-        [$( (($slf.0.s6_addr[$first] as u16) << 8) + $slf.0.s6_addr[$second] as u16,)*]
-    }
-}
-
 impl Ipv6Addr {
     pub fn new(a: u16, b: u16, c: u16, d: u16, e: u16, f: u16, g: u16, h: u16) -> Ipv6Addr {
         let mut in6_addr_var: libc::in6_addr = unsafe{mem::uninitialized()};
@@ -331,28 +1009,576 @@ impl Ipv6Addr {
         Ipv6Addr::new(s[0], s[1], s[2], s[3], s[4], s[5], s[6], s[7])
     }
 
+    /// Generate an address uniformly at random from `rng`.  With a seeded
+    /// `rng` the sequence of addresses is reproducible.
+    #[cfg(feature = "rand")]
+    pub fn from_rng<R: Rng + ?Sized>(rng: &mut R) -> Ipv6Addr {
+        let mut in6_addr_var: libc::in6_addr = unsafe { mem::zeroed() };
+        rng.fill_bytes(&mut in6_addr_var.s6_addr);
+        Ipv6Addr(in6_addr_var)
+    }
+
+    /// Create the link-local address (`fe80::/64`) whose interface
+    /// identifier is the modified EUI-64 derived from `mac`, as used by
+    /// stateless address autoconfiguration (RFC 4291, appendix A).
+    pub fn link_local_from_mac(mac: [u8; 6]) -> Ipv6Addr {
+        let mut in6_addr_var: libc::in6_addr = unsafe { mem::zeroed() };
+        in6_addr_var.s6_addr = [0xfe, 0x80, 0, 0, 0, 0, 0, 0,
+                                mac[0] ^ 0x02, mac[1], mac[2], 0xff,
+                                0xfe, mac[3], mac[4], mac[5]];
+        Ipv6Addr(in6_addr_var)
+    }
+
     /// Return the eight 16-bit segments that make up this address
     pub fn segments(&self) -> [u16; 8] {
-        to_u16_array!(self, 0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15)
+        let bytes = &self.0.s6_addr;
+        let mut segments = [0u16; 8];
+        for (i, seg) in segments.iter_mut().enumerate() {
+            *seg = (bytes[2 * i] as u16) << 8 | bytes[2 * i + 1] as u16;
+        }
+        segments
+    }
+
+    /// Return the sixteen bytes that make up this address, in network order
+    pub fn octets(&self) -> [u8; 16] {
+        self.0.s6_addr
     }
 
     pub fn to_std(&self) -> net::Ipv6Addr {
         let s = self.segments();
         net::Ipv6Addr::new(s[0], s[1], s[2], s[3], s[4], s[5], s[6], s[7])
     }
-}
 
-impl fmt::Display for Ipv6Addr {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        self.to_std().fmt(fmt)
+    /// Whether this is the loopback address `::1`
+    pub fn is_loopback(&self) -> bool {
+        self.segments() == [0, 0, 0, 0, 0, 0, 0, 1]
     }
-}
 
-/*
- *
- * ===== UnixAddr =====
- *
- */
+    /// Whether this is the unspecified address `::`
+    pub fn is_unspecified(&self) -> bool {
+        self.segments() == [0; 8]
+    }
+
+    /// Whether this is a multicast address (`ff00::/8`)
+    pub fn is_multicast(&self) -> bool {
+        self.octets()[0] == 0xff
+    }
+
+    /// Return the address as a `Vec` of 16 bytes in network (big-endian)
+    /// order, e.g. for a protobuf `bytes` field.
+    pub fn to_be_bytes_vec(&self) -> Vec<u8> {
+        self.octets().to_vec()
+    }
+
+    /// Create an address from 16 bytes in network (big-endian) order.  Fails
+    /// with `EINVAL` if `bytes` has any other length.
+    pub fn from_be_bytes_slice(bytes: &[u8]) -> Result<Ipv6Addr> {
+        if bytes.len() != 16 {
+            return Err(Error::invalid_argument());
+        }
+        let mut in6_addr_var: libc::in6_addr = unsafe { mem::zeroed() };
+        in6_addr_var.s6_addr.copy_from_slice(bytes);
+        Ok(Ipv6Addr(in6_addr_var))
+    }
+
+    /// Return whether this address is reserved for documentation, i.e. lies
+    /// in `2001:db8::/32` (RFC 3849).
+    pub fn is_documentation(&self) -> bool {
+        let s = self.segments();
+        s[0] == 0x2001 && s[1] == 0xdb8
+    }
+
+    /// If this address lies in the NAT64 well-known prefix `64:ff9b::/96`
+    /// (RFC 6052), return the IPv4 address embedded in its last 32 bits.
+    pub fn nat64_embedded_v4(&self) -> Option<Ipv4Addr> {
+        let s = self.segments();
+        if s[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+            let o = self.octets();
+            Some(Ipv4Addr::new(o[12], o[13], o[14], o[15]))
+        } else {
+            None
+        }
+    }
+
+    /// Classify this address against the IANA IPv6 special-purpose address
+    /// registry, plus multicast.  Returns `None` for ordinary global unicast
+    /// addresses.
+    pub fn special_purpose(&self) -> Option<SpecialPurpose> {
+        let s = self.segments();
+        let o = self.octets();
+        if s == [0; 8] {
+            Some(SpecialPurpose::Unspecified)
+        } else if s == [0, 0, 0, 0, 0, 0, 0, 1] {
+            Some(SpecialPurpose::Loopback)
+        } else if is_v4_mapped(&o) {
+            Some(SpecialPurpose::Ipv4Mapped)
+        } else if s[0] == 0x64 && s[1] == 0xff9b && (s[2] == 1 || s[2..6] == [0; 4]) {
+            Some(SpecialPurpose::Ipv4Translation)
+        } else if s[0] == 0x100 && s[1..4] == [0; 3] {
+            Some(SpecialPurpose::DiscardOnly)
+        } else if s[0] == 0x2001 && s[1] == 0 {
+            Some(SpecialPurpose::Teredo)
+        } else if s[0] == 0x2001 && s[1] == 2 && s[2] == 0 {
+            Some(SpecialPurpose::Benchmarking)
+        } else if s[0] == 0x2001 && (s[1] & 0xfff0 == 0x10 || s[1] & 0xfff0 == 0x20) {
+            Some(SpecialPurpose::Orchid)
+        } else if self.is_documentation() {
+            Some(SpecialPurpose::Documentation)
+        } else if s[0] == 0x2002 {
+            Some(SpecialPurpose::SixToFour)
+        } else if o[0] & 0xfe == 0xfc {
+            Some(SpecialPurpose::UniqueLocal)
+        } else if s[0] & 0xffc0 == 0xfe80 {
+            Some(SpecialPurpose::LinkLocal)
+        } else if o[0] == 0xff {
+            Some(SpecialPurpose::Multicast)
+        } else {
+            None
+        }
+    }
+
+    /// Return the number of leading bits this address has in common with
+    /// `other`, from 0 to 128.
+    pub fn common_prefix_len(&self, other: &Ipv6Addr) -> u8 {
+        let mut len = 0;
+        for (a, b) in self.0.s6_addr.iter().zip(other.0.s6_addr.iter()) {
+            let diff = a ^ b;
+            len += diff.leading_zeros() as u8;
+            if diff != 0 {
+                break;
+            }
+        }
+        len
+    }
+
+    /// Return the `/128` CIDR block that contains just this address
+    pub fn to_host_cidr(&self) -> CidrV6 {
+        CidrV6 { addr: *self, prefix_len: 128 }
+    }
+
+    /// Whether this address equals `bits`, given as its high and low 64 bits
+    /// in host byte order, e.g. `(0, 1)` for `::1`
+    pub fn eq_bits(&self, bits: (u64, u64)) -> bool {
+        let (high, low) = bits;
+        let b = &self.0.s6_addr;
+        (0..8).all(|i| {
+            let shift = 56 - 8 * i;
+            b[i] == (high >> shift) as u8 && b[8 + i] == (low >> shift) as u8
+        })
+    }
+
+    /// Return the labels of the reverse DNS (PTR) query name: one lowercase
+    /// hex digit per nibble, least significant first, followed by `"ip6"`
+    /// and `"arpa"`
+    pub fn reverse_dns_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self.to_hex_string().chars().rev()
+            .map(|c| c.to_string())
+            .collect();
+        labels.push("ip6".to_owned());
+        labels.push("arpa".to_owned());
+        labels
+    }
+
+    /// Return the address as 32 lowercase hex digits without separators, e.g.
+    /// `"00000000000000000000000000000001"` for `::1`
+    pub fn to_hex_string(&self) -> String {
+        self.0.s6_addr.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Parse the form produced by `to_hex_string`; upper case digits are
+    /// accepted too.  Fails with `EINVAL` unless `s` is exactly 32 hex digits.
+    pub fn from_hex_string(s: &str) -> Result<Ipv6Addr> {
+        if s.len() != 32 || !s.bytes().all(|b| (b as char).is_digit(16)) {
+            return Err(Error::invalid_argument());
+        }
+        let mut in6_addr_var: libc::in6_addr = unsafe { mem::zeroed() };
+        for (i, b) in in6_addr_var.s6_addr.iter_mut().enumerate() {
+            *b = try!(u8::from_str_radix(&s[2 * i..2 * i + 2], 16)
+                      .map_err(|_| Error::invalid_argument()));
+        }
+        Ok(Ipv6Addr(in6_addr_var))
+    }
+
+    /// Whether `other` immediately precedes or follows this address.  There
+    /// is no wraparound: the all-ones address and `::` are not adjacent.
+    pub fn is_adjacent(&self, other: &Ipv6Addr) -> bool {
+        let a = self.0.s6_addr;
+        let b = other.0.s6_addr;
+        v6_successor(a) == Some(b) || v6_successor(b) == Some(a)
+    }
+
+    /// Return the interface identifier, i.e. the low 64 bits
+    pub fn interface_id(&self) -> u64 {
+        self.0.s6_addr[8..].iter().fold(0, |id, &b| id << 8 | b as u64)
+    }
+
+    /// Whether both addresses lie in the same `/64`, i.e. share their upper
+    /// 64 bits
+    pub fn same_subnet64(&self, other: &Ipv6Addr) -> bool {
+        self.0.s6_addr[..8] == other.0.s6_addr[..8]
+    }
+
+    /// Return a copy of this address with the interface identifier (the low
+    /// 64 bits) replaced by `id`
+    pub fn with_interface_id(&self, id: u64) -> Ipv6Addr {
+        let mut ret = *self;
+        for (i, b) in ret.0.s6_addr[8..].iter_mut().enumerate() {
+            *b = (id >> (56 - 8 * i)) as u8;
+        }
+        ret
+    }
+
+    /// Return a copy of this address with the universal/local bit of the
+    /// interface identifier (`0x02` in byte 8) inverted
+    pub fn with_flipped_ul_bit(&self) -> Ipv6Addr {
+        let mut ret = *self;
+        ret.0.s6_addr[8] ^= 0x02;
+        ret
+    }
+
+    /// Whether the universal/local bit of the interface identifier is set.
+    /// In a modified EUI-64 identifier (RFC 4291) this means it was derived
+    /// from a globally unique MAC address.
+    pub fn is_universal_iid(&self) -> bool {
+        self.0.s6_addr[8] & 0x02 != 0
+    }
+
+    /// Whether this is the network (Subnet-Router anycast) address of its
+    /// `/prefix_len` subnet, i.e. all host bits are zero.  Always false for
+    /// prefixes of 127 bits or more, whose addresses are all usable by hosts
+    /// (RFC 6164).
+    pub fn is_network_address(&self, prefix_len: u8) -> bool {
+        prefix_len < 127 && self.network(prefix_len).0.s6_addr == self.0.s6_addr
+    }
+
+    /// Return the netmask for a prefix of `prefix_len` bits, e.g.
+    /// `ffff:ffff::` for 32.  A `prefix_len` above 128 saturates, giving the
+    /// all-ones address.
+    pub fn netmask(prefix_len: u8) -> Ipv6Addr {
+        let mut in6_addr_var: libc::in6_addr = unsafe { mem::zeroed() };
+        in6_addr_var.s6_addr = v6_mask(cmp::min(prefix_len, 128));
+        Ipv6Addr(in6_addr_var)
+    }
+
+    /// Return the network part of this address, i.e. the address with all
+    /// but the first `prefix_len` bits cleared.  A `prefix_len` above 128
+    /// saturates, returning the address unchanged.
+    pub fn network(&self, prefix_len: u8) -> Ipv6Addr {
+        let mask = v6_mask(cmp::min(prefix_len, 128));
+        let mut ret = *self;
+        for (byte, m) in ret.0.s6_addr.iter_mut().zip(mask.iter()) {
+            *byte &= *m;
+        }
+        ret
+    }
+}
+
+/// Categories of special-purpose IPv6 addresses, see
+/// `Ipv6Addr::special_purpose`
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum SpecialPurpose {
+    /// `::/128`
+    Unspecified,
+    /// `::1/128`
+    Loopback,
+    /// `::ffff:0:0/96`
+    Ipv4Mapped,
+    /// `64:ff9b::/96` and `64:ff9b:1::/48` (RFC 6052, RFC 8215)
+    Ipv4Translation,
+    /// `100::/64` (RFC 6666)
+    DiscardOnly,
+    /// `2001::/32` (RFC 4380)
+    Teredo,
+    /// `2001:2::/48` (RFC 5180)
+    Benchmarking,
+    /// `2001:10::/28` and `2001:20::/28` (RFC 4843, RFC 7343)
+    Orchid,
+    /// `2001:db8::/32` (RFC 3849)
+    Documentation,
+    /// `2002::/16` (RFC 3056)
+    SixToFour,
+    /// `fc00::/7` (RFC 4193)
+    UniqueLocal,
+    /// `fe80::/10` (RFC 4291)
+    LinkLocal,
+    /// `ff00::/8` (RFC 4291)
+    Multicast,
+}
+
+/// Whether `bytes` is an IPv4-mapped IPv6 address, `::ffff:0:0/96`
+fn is_v4_mapped(bytes: &[u8; 16]) -> bool {
+    bytes[..10].iter().all(|&b| b == 0) && bytes[10] == 0xff && bytes[11] == 0xff
+}
+
+/// The IP of `addr`, with IPv4-mapped IPv6 addresses turned into IPv4
+fn unmapped_ip(addr: &InetAddr) -> net::IpAddr {
+    match addr.to_std().ip() {
+        net::IpAddr::V6(ip) if is_v4_mapped(&ip.octets()) => {
+            let o = ip.octets();
+            net::IpAddr::V4(net::Ipv4Addr::new(o[12], o[13], o[14], o[15]))
+        }
+        ip => ip,
+    }
+}
+
+/// See `SockAddr::conflicts_with`
+fn inet_bind_conflict(a: &InetAddr, b: &InetAddr) -> bool {
+    if a.port() == 0 || a.port() != b.port() {
+        return false;
+    }
+    match (unmapped_ip(a), unmapped_ip(b)) {
+        (net::IpAddr::V4(x), net::IpAddr::V4(y)) => {
+            x == y || x.is_unspecified() || y.is_unspecified()
+        }
+        (net::IpAddr::V6(x), net::IpAddr::V6(y)) => {
+            x.is_unspecified() || y.is_unspecified() || a.ip_eq(b)
+        }
+        (net::IpAddr::V6(x), net::IpAddr::V4(_)) |
+        (net::IpAddr::V4(_), net::IpAddr::V6(x)) => x.is_unspecified(),
+    }
+}
+
+impl From<net::Ipv6Addr> for Ipv6Addr {
+    fn from(std: net::Ipv6Addr) -> Ipv6Addr {
+        Ipv6Addr::from_std(&std)
+    }
+}
+
+impl From<Ipv6Addr> for net::Ipv6Addr {
+    fn from(addr: Ipv6Addr) -> net::Ipv6Addr {
+        addr.to_std()
+    }
+}
+
+impl PartialEq for Ipv6Addr {
+    fn eq(&self, other: &Ipv6Addr) -> bool {
+        self.0.s6_addr == other.0.s6_addr
+    }
+}
+
+impl Eq for Ipv6Addr {
+}
+
+impl hash::Hash for Ipv6Addr {
+    fn hash<H: hash::Hasher>(&self, s: &mut H) {
+        self.0.s6_addr.hash(s)
+    }
+}
+
+impl PartialEq<[u8; 16]> for Ipv6Addr {
+    fn eq(&self, other: &[u8; 16]) -> bool {
+        self.0.s6_addr == *other
+    }
+}
+
+impl PartialEq<Ipv6Addr> for [u8; 16] {
+    fn eq(&self, other: &Ipv6Addr) -> bool {
+        *self == other.0.s6_addr
+    }
+}
+
+impl fmt::Display for Ipv6Addr {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.to_std().fmt(fmt)
+    }
+}
+
+impl FromStr for Ipv6Addr {
+    type Err = Error;
+
+    /// Parse an address in the usual colon-separated notation, optionally
+    /// enclosed in brackets as in URL authorities (`[::1]`).  Fails with
+    /// `EINVAL` if `s` isn't one, or if the brackets don't match.  Only ASCII
+    /// hex digits are accepted, never Unicode lookalikes.
+    fn from_str(s: &str) -> Result<Ipv6Addr> {
+        if !s.bytes().all(|b| b < 0x80) {
+            return Err(Error::invalid_argument());
+        }
+        let bare = if s.starts_with('[') && s.ends_with(']') {
+            &s[1..s.len() - 1]
+        } else {
+            s
+        };
+        match bare.parse::<net::Ipv6Addr>() {
+            Ok(std) => Ok(Ipv6Addr::from_std(&std)),
+            Err(_) => Err(Error::invalid_argument()),
+        }
+    }
+}
+
+/// Formats the address as 128 binary digits.  The alternate form (`{:#b}`)
+/// separates the 16-bit segments with colons.
+impl fmt::Binary for Ipv6Addr {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for (i, segment) in self.segments().iter().enumerate() {
+            if i > 0 && fmt.alternate() {
+                try!(fmt.write_str(":"));
+            }
+            try!(write!(fmt, "{:016b}", segment));
+        }
+        Ok(())
+    }
+}
+
+/*
+ *
+ * ===== Cidr =====
+ *
+ */
+
+/// The netmask for an IPv4 prefix of `prefix_len` bits, in host byte order.
+/// `prefix_len` must be at most 32.
+fn v4_mask(prefix_len: u8) -> u32 {
+    if prefix_len == 0 {
+        0
+    } else {
+        !0u32 << (32 - prefix_len as u32)
+    }
+}
+
+/// The address following `bytes`, or `None` for the all-ones address
+fn v6_successor(mut bytes: [u8; 16]) -> Option<[u8; 16]> {
+    for b in bytes.iter_mut().rev() {
+        if *b == 0xff {
+            *b = 0;
+        } else {
+            *b += 1;
+            return Some(bytes);
+        }
+    }
+    None
+}
+
+/// The netmask for an IPv6 prefix of `prefix_len` bits.  `prefix_len` must be
+/// at most 128.
+fn v6_mask(prefix_len: u8) -> [u8; 16] {
+    let mut mask = [0u8; 16];
+    for (i, byte) in mask.iter_mut().enumerate() {
+        let bits = prefix_len as isize - 8 * i as isize;
+        *byte = if bits >= 8 {
+            0xff
+        } else if bits <= 0 {
+            0
+        } else {
+            !0u8 << (8 - bits as u32)
+        };
+    }
+    mask
+}
+
+/// An IPv4 network, given by an address and a prefix length, like
+/// `192.168.0.0/16`
+#[derive(Clone, Copy)]
+pub struct CidrV4 {
+    addr: Ipv4Addr,
+    prefix_len: u8,
+}
+
+impl CidrV4 {
+    /// Create a new `CidrV4`.  Fails with `EINVAL` if `prefix_len` is greater
+    /// than 32.
+    pub fn new(addr: Ipv4Addr, prefix_len: u8) -> Result<CidrV4> {
+        if prefix_len > 32 {
+            return Err(Error::invalid_argument());
+        }
+        Ok(CidrV4 { addr: addr, prefix_len: prefix_len })
+    }
+
+    pub fn addr(&self) -> Ipv4Addr {
+        self.addr
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Return whether `ip` lies within this network
+    pub fn contains(&self, ip: &Ipv4Addr) -> bool {
+        let mask = v4_mask(self.prefix_len);
+        u32::from_be(ip.0.s_addr) & mask == u32::from_be(self.addr.0.s_addr) & mask
+    }
+}
+
+impl fmt::Display for CidrV4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// An IPv6 network, given by an address and a prefix length, like
+/// `2001:db8::/32`
+#[derive(Clone, Copy)]
+pub struct CidrV6 {
+    addr: Ipv6Addr,
+    prefix_len: u8,
+}
+
+impl CidrV6 {
+    /// Create a new `CidrV6`.  Fails with `EINVAL` if `prefix_len` is greater
+    /// than 128.
+    pub fn new(addr: Ipv6Addr, prefix_len: u8) -> Result<CidrV6> {
+        if prefix_len > 128 {
+            return Err(Error::invalid_argument());
+        }
+        Ok(CidrV6 { addr: addr, prefix_len: prefix_len })
+    }
+
+    pub fn addr(&self) -> Ipv6Addr {
+        self.addr
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Return whether `ip` lies within this network
+    pub fn contains(&self, ip: &Ipv6Addr) -> bool {
+        let mask = v6_mask(self.prefix_len);
+        let a = &ip.0.s6_addr;
+        let b = &self.addr.0.s6_addr;
+        (0..16).all(|i| a[i] & mask[i] == b[i] & mask[i])
+    }
+}
+
+impl fmt::Display for CidrV6 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// An IPv4 or IPv6 network
+#[derive(Clone, Copy)]
+pub enum Cidr {
+    V4(CidrV4),
+    V6(CidrV6),
+}
+
+impl Cidr {
+    /// Return whether `ip` lies within this network.  Addresses of the other
+    /// family are never contained.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (*self, ip) {
+            (Cidr::V4(ref cidr), &IpAddr::V4(ref ip)) => cidr.contains(ip),
+            (Cidr::V6(ref cidr), &IpAddr::V6(ref ip)) => cidr.contains(ip),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Cidr::V4(ref cidr) => cidr.fmt(f),
+            Cidr::V6(ref cidr) => cidr.fmt(f),
+        }
+    }
+}
+
+/*
+ *
+ * ===== UnixAddr =====
+ *
+ */
 
 /// A wrapper around `sockaddr_un`. We track the length of `sun_path` (excluding
 /// a terminating null), because it may not be null-terminated.  For example,
@@ -363,7 +1589,23 @@ impl fmt::Display for Ipv6Addr {
 #[derive(Copy)]
 pub struct UnixAddr(pub libc::sockaddr_un, pub usize);
 
+/// The longest name that `UnixAddr::new_abstract` accepts.  This is one byte
+/// less than the size of `sockaddr_un::sun_path`, because abstract addresses
+/// start with a null byte.
+///
+/// This is a module-level constant rather than an associated one because
+/// associated constants need Rust 1.20.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const MAX_ABSTRACT_LEN: usize = 107;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const MAX_ABSTRACT_LEN: usize = 103;
+
 impl UnixAddr {
+    /// Return whether `name` is short enough to be used with `new_abstract`.
+    pub fn abstract_fits(name: &[u8]) -> bool {
+        name.len() <= MAX_ABSTRACT_LEN
+    }
+
     /// Create a new sockaddr_un representing a filesystem path.
     pub fn new<P: ?Sized + NixPath>(path: &P) -> Result<UnixAddr> {
         try!(path.with_nix_path(|cstr| {
@@ -388,6 +1630,37 @@ impl UnixAddr {
         }))
     }
 
+    /// Like `new`, but fail with `EISDIR` if the path ends with a slash.  Such
+    /// a path names a directory, so it can never be a socket, but `new` lets
+    /// it through and leaves the kernel to reject it at `bind` or `connect`.
+    pub fn new_strict<P: ?Sized + NixPath>(path: &P) -> Result<UnixAddr> {
+        let addr = try!(UnixAddr::new(path));
+        if addr.sun_path().last() == Some(&b'/') {
+            return Err(Error::Sys(Errno::EISDIR));
+        }
+        Ok(addr)
+    }
+
+    /// Return a filesystem path address for `path`, whatever kind of address
+    /// `self` is.  Fails with `ENAMETOOLONG` if `path` doesn't fit.
+    pub fn with_path(&self, path: &Path) -> Result<UnixAddr> {
+        UnixAddr::new(path)
+    }
+
+    /// Create the address of a socket named `sock` in a fresh, private
+    /// directory below `std::env::temp_dir()`, for tests that need a path to
+    /// bind both ends of a Unix connection to.  The directory, and whatever
+    /// is bound in it, is removed when the returned guard is dropped.
+    ///
+    /// Paths are unique per process and call.  Fails with `ENAMETOOLONG` if
+    /// the temporary directory is too deep for `sun_path`.
+    #[cfg(feature = "test-util")]
+    pub fn pair_in_tempdir() -> Result<(UnixAddr, TempSocketDir)> {
+        let dir = try!(TempSocketDir::new());
+        let addr = try!(UnixAddr::new(&dir.path().join("sock")));
+        Ok((addr, dir))
+    }
+
     /// Create a new sockaddr_un representing an address in the
     /// "abstract namespace". This is a Linux-specific extension,
     /// primarily used to allow chrooted processes to communicate with
@@ -399,7 +1672,7 @@ impl UnixAddr {
                 .. mem::zeroed()
             };
 
-            if path.len() + 1 > ret.sun_path.len() {
+            if !UnixAddr::abstract_fits(path) {
                 return Err(Error::Sys(Errno::ENAMETOOLONG));
             }
 
@@ -413,10 +1686,78 @@ impl UnixAddr {
         }
     }
 
+    /// Create an abstract address named by the path-like string `name`, such
+    /// as `"myapp/worker/control"`, for code that wants filesystem-style
+    /// names without files on disk.
+    ///
+    /// The name is used verbatim, so distinct names always give distinct
+    /// addresses; it is never hashed or truncated to make it fit.  Fails
+    /// with `ENAMETOOLONG` if `name` is longer than `MAX_ABSTRACT_LEN`
+    /// bytes, and with `EINVAL` if it is empty or contains a null byte.
+    pub fn abstract_from_path_like(name: &str) -> Result<UnixAddr> {
+        if name.is_empty() || name.as_bytes().contains(&0) {
+            return Err(Error::invalid_argument());
+        }
+        UnixAddr::new_abstract(name.as_bytes())
+    }
+
+    /// Create the address of the systemd notification socket from the value
+    /// of `$NOTIFY_SOCKET`.  Following the systemd convention, a leading `@`
+    /// denotes a name in the Linux abstract namespace; anything else is a
+    /// filesystem path.  Fails with `EINVAL` for an empty value, or for an
+    /// abstract name on other platforms.
+    pub fn from_systemd_notify(value: &[u8]) -> Result<UnixAddr> {
+        match value.first() {
+            None => Err(Error::invalid_argument()),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Some(&b'@') => UnixAddr::new_abstract(&value[1..]),
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            Some(&b'@') => Err(Error::invalid_argument()),
+            Some(_) => UnixAddr::new(value),
+        }
+    }
+
+    /// Return the raw `sun_family` field.  For an address that came from the
+    /// kernel or other untrusted source, check that this is `AF_UNIX` before
+    /// relying on the rest of the structure.
+    pub fn sun_family(&self) -> sa_family_t {
+        self.0.sun_family
+    }
+
+    /// Return the length to pass along with a pointer to the raw
+    /// `sockaddr_un`, as `SockAddr::as_ffi_pair` does for Unix addresses.
+    pub fn ffi_len(&self) -> libc::socklen_t {
+        (self.1 + unsafe { offset_of!(libc::sockaddr_un, sun_path) }) as libc::socklen_t
+    }
+
     fn sun_path(&self) -> &[u8] {
         unsafe { mem::transmute(&self.0.sun_path[..self.1]) }
     }
 
+    /// Rank the kind of address for keys: unnamed, abstract, then path
+    fn kind_rank(&self) -> u8 {
+        if self.is_unnamed() {
+            0
+        } else if self.is_abstract() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Whether this is an unnamed address, as of a socket that was never
+    /// bound
+    pub fn is_unnamed(&self) -> bool {
+        self.1 == 0
+    }
+
+    /// Whether this is an address in the Linux abstract namespace.  Always
+    /// false on other platforms.
+    pub fn is_abstract(&self) -> bool {
+        cfg!(any(target_os = "linux", target_os = "android")) &&
+            self.1 > 0 && self.0.sun_path[0] == 0
+    }
+
     /// If this address represents a filesystem path, return that path.
     pub fn path(&self) -> Option<&Path> {
         if self.1 == 0 || self.0.sun_path[0] == 0 {
@@ -433,11 +1774,145 @@ impl UnixAddr {
             Some(Path::new(<OsStr as OsStrExt>::from_bytes(&p[..reallen])))
         }
     }
+
+    /// If this is an address in the Linux abstract namespace, return its name
+    /// without the leading null byte.
+    ///
+    /// Telling abstract, unnamed and path addresses apart needs the address
+    /// length the kernel reported.  Convert raw addresses with the
+    /// length-aware `SockAddr::from_msghdr_name` or
+    /// `SockAddr::from_libc_sockaddr_exact` to get a meaningful answer.
+    pub fn as_abstract(&self) -> Option<&[u8]> {
+        if self.is_abstract() {
+            Some(&self.sun_path()[1..])
+        } else {
+            None
+        }
+    }
+
+    /// Whether both addresses are paths that resolve to the same file, after
+    /// following symlinks.  Unlike `==` this touches the filesystem, so it
+    /// fails if either path can't be resolved.  Abstract and unnamed
+    /// addresses yield `Ok(false)`.
+    pub fn same_file(&self, other: &UnixAddr) -> io::Result<bool> {
+        match (self.path(), other.path()) {
+            (Some(a), Some(b)) => Ok(try!(fs::canonicalize(a)) == try!(fs::canonicalize(b))),
+            _ => Ok(false),
+        }
+    }
+
+    /// Return the directory containing this address's socket file, as
+    /// `Path::parent` does.  `None` for abstract or unnamed addresses, and
+    /// for `/`.
+    pub fn parent_dir(&self) -> Option<&Path> {
+        self.path().and_then(Path::parent)
+    }
+
+    /// Return whether this address's path matches the glob `pattern`, in
+    /// which `*` matches any run of characters and `?` any single character,
+    /// except that neither matches a `/`.  Other characters, including `[`,
+    /// match only themselves.  Abstract and unnamed addresses never match.
+    pub fn matches_glob(&self, pattern: &str) -> bool {
+        match self.path() {
+            Some(path) => glob_match(pattern.as_bytes(), path.as_os_str().as_bytes()),
+            None => false,
+        }
+    }
+
+    /// Format this address like `Display` does, but if it's a path longer
+    /// than `max_len` characters, replace the middle with `...` so that the
+    /// result is `max_len` characters long, e.g. `/run/.../x.sock`.
+    /// Abstract and unnamed addresses are never shortened.
+    pub fn display_truncated(&self, max_len: usize) -> String {
+        let full = self.to_string();
+        let chars: Vec<char> = full.chars().collect();
+        if self.path().is_none() || chars.len() <= max_len {
+            return full;
+        }
+        let avail = max_len.saturating_sub(3);
+        let tail = avail / 2;
+        let head = avail - tail;
+        let mut ret: String = chars[..head].iter().cloned().collect();
+        ret.push_str(&"..."[..cmp::min(max_len, 3)]);
+        ret.extend(chars[chars.len() - tail..].iter().cloned());
+        ret
+    }
+
+    /// Whether this is an abstract address whose name, without the leading
+    /// null byte, starts with `prefix`.  Path and unnamed addresses never
+    /// match.
+    pub fn abstract_has_prefix(&self, prefix: &[u8]) -> bool {
+        let path = self.sun_path();
+        path.first() == Some(&0) && path[1..].starts_with(prefix)
+    }
+
+    /// Parse the tagged form produced by `format!("{:#}", addr)`: `unix:`
+    /// followed by either a path, `@` and an abstract name, or nothing at all
+    /// for an unnamed address.
+    ///
+    /// The round trip is lossless for paths and names that are valid UTF-8.
+    /// A relative path that starts with `@` must be written as `./@...`, or
+    /// it will be taken for an abstract name.
+    pub fn from_tagged_string(s: &str) -> Result<UnixAddr> {
+        if !s.starts_with("unix:") {
+            return Err(Error::invalid_argument());
+        }
+        let rest = &s["unix:".len()..];
+        if rest.starts_with('@') {
+            UnixAddr::new_abstract(rest[1..].as_bytes())
+        } else {
+            // An empty path yields an unnamed address
+            UnixAddr::new(rest)
+        }
+    }
+}
+
+/// Match `name` against `pattern` for `UnixAddr::matches_glob`
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // Where to resume after the most recent `*` if the rest fails to match
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, n));
+            p += 1;
+            continue;
+        }
+        if p < pattern.len() && name[n] != b'/' &&
+            (pattern[p] == b'?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+            continue;
+        }
+        if p < pattern.len() && pattern[p] == b'/' && name[n] == b'/' {
+            p += 1;
+            n += 1;
+            continue;
+        }
+        match backtrack {
+            // Let the `*` swallow one more character, unless that's a `/`
+            Some((star, matched)) if name[matched] != b'/' => {
+                p = star + 1;
+                n = matched + 1;
+                backtrack = Some((star, n));
+            }
+            _ => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
 }
 
+/// Addresses of different kinds (path, abstract or unnamed) never compare
+/// equal.  The kind is a function of the raw bytes, since an abstract name is
+/// marked by a leading null that no path can start with, but the check is
+/// made explicitly rather than left implicit in the byte comparison.  The
+/// family field is compared as well, consistent with the `Hash` impl.
 impl PartialEq for UnixAddr {
     fn eq(&self, other: &UnixAddr) -> bool {
-        self.sun_path() == other.sun_path()
+        self.is_abstract() == other.is_abstract() &&
+            self.is_unnamed() == other.is_unnamed() &&
+            self.0.sun_family == other.0.sun_family &&
+            self.sun_path() == other.sun_path()
     }
 }
 
@@ -454,82 +1929,824 @@ impl Clone for UnixAddr {
     fn clone(&self) -> UnixAddr {
         *self
     }
-}
+}
+
+/// The alternate form (`{:#}`) is tagged with a `unix:` prefix and can be
+/// parsed back with `UnixAddr::from_tagged_string`.
+impl fmt::Display for UnixAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            try!(f.write_str("unix:"));
+        }
+        if self.1 == 0 {
+            if f.alternate() {
+                Ok(())
+            } else {
+                f.write_str("<unbound UNIX socket>")
+            }
+        } else if let Some(path) = self.path() {
+            path.display().fmt(f)
+        } else {
+            let display = String::from_utf8_lossy(&self.sun_path()[1..]);
+            write!(f, "@{}", display)
+        }
+    }
+}
+
+/*
+ *
+ * ===== Sock addr =====
+ *
+ */
+
+/// Represents a socket address
+#[derive(Copy)]
+pub enum SockAddr {
+    Inet(InetAddr),
+    Unix(UnixAddr),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Netlink(NetlinkAddr),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Key(KeyAddr),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Vsock(VsockAddr),
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    Xdp(XdpAddr),
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    SysControl(SysControlAddr),
+    #[cfg(any(target_os = "macos",
+              target_os = "ios",
+              target_os = "freebsd",
+              target_os = "dragonfly",
+              target_os = "openbsd",
+              target_os = "netbsd"))]
+    Link(LinkAddr),
+}
+
+impl SockAddr {
+    pub fn new_inet(addr: InetAddr) -> SockAddr {
+        SockAddr::Inet(addr)
+    }
+
+    /// Create an IPv6 address with the scope id set to `ifindex`, as needed
+    /// to connect to a link-local peer.
+    pub fn new_inet_scoped(ip: Ipv6Addr, port: u16, ifindex: u32) -> SockAddr {
+        let mut inet = InetAddr::new(IpAddr::V6(ip), port);
+        if let InetAddr::V6(ref mut sa) = inet {
+            sa.sin6_scope_id = ifindex;
+        }
+        SockAddr::Inet(inet)
+    }
+
+    pub fn new_unix<P: ?Sized + NixPath>(path: &P) -> Result<SockAddr> {
+        Ok(SockAddr::Unix(try!(UnixAddr::new(path))))
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn new_netlink(pid: u32, groups: u32) -> SockAddr {
+        SockAddr::Netlink(NetlinkAddr::new(pid, groups))
+    }
+
+    /// Create an `AF_KEY` address.  See `KeyAddr::new`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn new_key() -> SockAddr {
+        SockAddr::Key(KeyAddr::new())
+    }
+
+    /// Create an `AF_VSOCK` address for `port` on the virtual machine with
+    /// context id `cid`.  See `VsockAddr::new`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn new_vsock(cid: u32, port: u32) -> SockAddr {
+        SockAddr::Vsock(VsockAddr::new(cid, port))
+    }
+
+    /// Create an `AF_XDP` address for queue `queue_id` of the interface with
+    /// index `ifindex`.  See `XdpAddr::new`.
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    pub fn new_xdp(ifindex: u32, queue_id: u32, flags: u16, shared_umem_fd: RawFd) -> SockAddr {
+        SockAddr::Xdp(XdpAddr::new(ifindex, queue_id, flags, shared_umem_fd))
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub fn new_sys_control(sockfd: RawFd, name: &str, unit: u32) -> Result<SockAddr> {
+        SysControlAddr::from_name(sockfd, name, unit).map(|a| SockAddr::SysControl(a))
+    }
+
+    /// Create a link-layer address for the interface with index `ifindex` and
+    /// hardware address `addr`.
+    #[cfg(any(target_os = "macos",
+              target_os = "ios",
+              target_os = "freebsd",
+              target_os = "dragonfly",
+              target_os = "openbsd",
+              target_os = "netbsd"))]
+    pub fn new_link(ifindex: u16, addr: &[u8]) -> Result<SockAddr> {
+        LinkAddr::new(ifindex, addr).map(SockAddr::Link)
+    }
+
+    pub fn family(&self) -> AddressFamily {
+        match *self {
+            SockAddr::Inet(InetAddr::V4(..)) => AddressFamily::Inet,
+            SockAddr::Inet(InetAddr::V6(..)) => AddressFamily::Inet6,
+            SockAddr::Unix(..) => AddressFamily::Unix,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Netlink(..) => AddressFamily::Netlink,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Key(..) => AddressFamily::Key,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Vsock(..) => AddressFamily::Vsock,
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            SockAddr::Xdp(..) => AddressFamily::Xdp,
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            SockAddr::SysControl(..) => AddressFamily::System,
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            SockAddr::Link(..) => AddressFamily::Link,
+        }
+    }
+
+    /// Check that this address is structurally consistent, e.g. after
+    /// building it from raw parts and before passing it to a syscall: the
+    /// family field of the underlying structure must match the variant, the
+    /// length of a Unix address must fit into `sun_path`, and the name and
+    /// address of a link-layer address must fit into `sdl_data`.  Fails with
+    /// `EINVAL` otherwise.
+    pub fn validate(&self) -> Result<()> {
+        let (raw_family, fits) = match *self {
+            SockAddr::Inet(InetAddr::V4(ref sa)) => (sa.sin_family as i32, true),
+            SockAddr::Inet(InetAddr::V6(ref sa)) => (sa.sin6_family as i32, true),
+            SockAddr::Unix(ref unix) => {
+                (unix.0.sun_family as i32, unix.1 <= unix.0.sun_path.len())
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Netlink(ref nl) => (nl.0.nl_family as i32, true),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Key(ref key) => (key.0.sa_family as i32, true),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Vsock(ref vsock) => (vsock.0.svm_family as i32, true),
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            SockAddr::Xdp(ref xdp) => (xdp.0.sxdp_family as i32, true),
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            SockAddr::SysControl(ref sc) => (sc.0.sc_family as i32, true),
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            SockAddr::Link(ref link) => {
+                let sdl = &link.0;
+                let used = sdl.sdl_nlen as usize + sdl.sdl_alen as usize + sdl.sdl_slen as usize;
+                (sdl.sdl_family as i32, used <= sdl.sdl_data.len())
+            }
+        };
+        if raw_family != self.family() as i32 || !fits {
+            return Err(Error::invalid_argument());
+        }
+        Ok(())
+    }
+
+    /// Return the name of this address's family, like `"AF_INET"`.  Same as
+    /// `self.family().as_str()`, but with a single match.
+    pub fn family_name(&self) -> &'static str {
+        match *self {
+            SockAddr::Inet(InetAddr::V4(..)) => "AF_INET",
+            SockAddr::Inet(InetAddr::V6(..)) => "AF_INET6",
+            SockAddr::Unix(..) => "AF_UNIX",
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Netlink(..) => "AF_NETLINK",
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Key(..) => "AF_KEY",
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Vsock(..) => "AF_VSOCK",
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            SockAddr::Xdp(..) => "AF_XDP",
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            SockAddr::SysControl(..) => "AF_SYSTEM",
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            SockAddr::Link(..) => "AF_LINK",
+        }
+    }
+
+    pub fn to_str(&self) -> String {
+        format!("{}", self)
+    }
+
+    /// Return the IP and port of an internet address, or `None` for other
+    /// families.
+    pub fn inet_parts(&self) -> Option<(IpAddr, u16)> {
+        match *self {
+            SockAddr::Inet(ref inet) => Some((inet.ip(), inet.port())),
+            _ => None,
+        }
+    }
+
+    /// Return the IP of an internet address together with its scope id, the
+    /// latter `None` for IPv4 addresses and IPv6 addresses without a scope.
+    /// Returns `None` for other families.
+    pub fn ip_and_scope(&self) -> Option<(IpAddr, Option<u32>)> {
+        match *self {
+            SockAddr::Inet(InetAddr::V4(ref sa)) => Some((IpAddr::V4(Ipv4Addr(sa.sin_addr)), None)),
+            SockAddr::Inet(InetAddr::V6(ref sa)) => {
+                let scope = if sa.sin6_scope_id == 0 { None } else { Some(sa.sin6_scope_id) };
+                Some((IpAddr::V6(Ipv6Addr(sa.sin6_addr)), scope))
+            }
+            _ => None,
+        }
+    }
+
+    /// Return a description of this address for error messages, like
+    /// `IPv4 127.0.0.1:80` or `UNIX socket /tmp/s`.  The transport protocol
+    /// isn't part of an address, so internet addresses are described by IP
+    /// version only.
+    pub fn describe(&self) -> String {
+        match *self {
+            SockAddr::Inet(InetAddr::V4(..)) => format!("IPv4 {}", self),
+            SockAddr::Inet(InetAddr::V6(..)) => format!("IPv6 {}", self),
+            SockAddr::Unix(ref unix) if unix.1 == 0 => String::from("unnamed UNIX socket"),
+            SockAddr::Unix(..) => format!("UNIX socket {}", self),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Netlink(..) => format!("netlink socket {}", self),
+            _ => format!("{} socket {}", self.family_name(), self),
+        }
+    }
+
+    /// Return a zeroed `sockaddr_storage` with its family set to `family`,
+    /// together with the size of that family's address structure.
+    ///
+    /// The buffer itself is always a full `sockaddr_storage`; only the
+    /// returned length is narrowed.  Passed as the address length to
+    /// `recvfrom` or `accept`, it limits how many bytes the kernel copies
+    /// out, and a longer address is truncated rather than rejected.  It does
+    /// not save any stack: a buffer chosen by a runtime `family` can't be
+    /// smaller than the largest one, so callers that care should zero the
+    /// family's own structure, such as `libc::sockaddr_nl`, instead.
+    ///
+    /// `AddressFamily::Packet` gets the size of `sockaddr_ll`, but
+    /// `sockaddr_storage_to_addr` doesn't convert such addresses, so that
+    /// buffer can only be inspected raw.
+    pub fn recv_buffer(family: AddressFamily) -> (libc::sockaddr_storage, libc::socklen_t) {
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        storage.ss_family = family.to_i32() as sa_family_t;
+        (storage, SockAddr::struct_len(family) as libc::socklen_t)
+    }
+
+    /// The size of the address structure of `family`
+    fn struct_len(family: AddressFamily) -> usize {
+        match family {
+            AddressFamily::Inet => mem::size_of::<libc::sockaddr_in>(),
+            AddressFamily::Inet6 => mem::size_of::<libc::sockaddr_in6>(),
+            AddressFamily::Unix => mem::size_of::<libc::sockaddr_un>(),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Netlink => mem::size_of::<libc::sockaddr_nl>(),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Key => mem::size_of::<libc::sockaddr>(),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Vsock => mem::size_of::<libc::sockaddr_vm>(),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressFamily::Packet => mem::size_of::<libc::sockaddr_ll>(),
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            AddressFamily::Xdp => mem::size_of::<libc::sockaddr_xdp>(),
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            AddressFamily::System => mem::size_of::<sys_control::sockaddr_ctl>(),
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            AddressFamily::Link => mem::size_of::<libc::sockaddr_dl>(),
+        }
+    }
+
+    /// Convert the source address that `recvmsg` stores in `msg_name`, given
+    /// the `msg_namelen` the kernel reported.  Returns `None` if no address
+    /// was stored, or if it is malformed or of an unsupported family.
+    ///
+    /// This copies the address, so the buffer behind `addr` may be reused as
+    /// soon as this returns.
+    pub unsafe fn from_msghdr_name(addr: *const libc::sockaddr,
+                                   len: libc::socklen_t) -> Option<SockAddr> {
+        let len = len as usize;
+        if addr.is_null() || len > mem::size_of::<libc::sockaddr_storage>() {
+            return None;
+        }
+        let mut storage: libc::sockaddr_storage = mem::zeroed();
+        ptr::copy_nonoverlapping(addr as *const u8,
+                                 &mut storage as *mut _ as *mut u8,
+                                 len);
+        super::sockaddr_storage_to_addr(&storage, len).ok()
+    }
+
+    /// Like `from_msghdr_name`, but strict about the length: fail with
+    /// `EINVAL` unless `len` is exactly the size of the address structure of
+    /// the family found at `addr`, so that truncated addresses and ones with
+    /// trailing bytes are rejected.  Unix and link-layer addresses are
+    /// legitimately variable in length and are only checked as
+    /// `sockaddr_storage_to_addr` does.  Fails with `EAFNOSUPPORT` for an
+    /// unsupported family.
+    pub unsafe fn from_libc_sockaddr_exact(addr: *const libc::sockaddr,
+                                           len: libc::socklen_t) -> Result<SockAddr> {
+        let len = len as usize;
+        let family_end = offset_of!(libc::sockaddr, sa_family) + mem::size_of::<sa_family_t>();
+        if addr.is_null() || len < family_end || len > mem::size_of::<libc::sockaddr_storage>() {
+            return Err(Error::invalid_argument());
+        }
+        match AddressFamily::from_i32((*addr).sa_family as i32) {
+            None => return Err(Error::Sys(Errno::EAFNOSUPPORT)),
+            Some(AddressFamily::Unix) => {}
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            Some(AddressFamily::Link) => {}
+            Some(family) => if len != SockAddr::struct_len(family) {
+                return Err(Error::invalid_argument());
+            },
+        }
+        let mut storage: libc::sockaddr_storage = mem::zeroed();
+        ptr::copy_nonoverlapping(addr as *const u8,
+                                 &mut storage as *mut _ as *mut u8,
+                                 len);
+        super::sockaddr_storage_to_addr(&storage, len)
+    }
+
+    /// Return the raw `sockaddr` bytes of this address, as passed to the
+    /// kernel by `as_ffi_pair`.  The family field is in host byte order, so
+    /// the result is only meaningful on the same platform; `from_bytes`
+    /// turns it back into an equal `SockAddr`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        unsafe {
+            let (sa, len) = self.as_ffi_pair();
+            slice::from_raw_parts(sa as *const _ as *const u8, len as usize).to_vec()
+        }
+    }
+
+    /// Return the number of bytes `to_bytes` produces for this address, e.g.
+    /// to size a buffer up front
+    pub fn serialized_size(&self) -> usize {
+        unsafe { self.as_ffi_pair().1 as usize }
+    }
+
+    /// Convert raw `sockaddr` bytes, as produced by `to_bytes`, back into a
+    /// `SockAddr`.  Fails like `sockaddr_storage_to_addr`, and with `EINVAL`
+    /// if `bytes` is longer than a `sockaddr_storage`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SockAddr> {
+        if bytes.len() > mem::size_of::<libc::sockaddr_storage>() {
+            return Err(Error::invalid_argument());
+        }
+        unsafe {
+            let mut storage: libc::sockaddr_storage = mem::zeroed();
+            ptr::copy_nonoverlapping(bytes.as_ptr(),
+                                     &mut storage as *mut _ as *mut u8,
+                                     bytes.len());
+            super::sockaddr_storage_to_addr(&storage, bytes.len())
+        }
+    }
+
+    /// Convert the peer address that `accept` wrote into `storage`, given the
+    /// address length it reported.  Returns `None` if the address is
+    /// malformed or of an unsupported family.
+    ///
+    /// Unix peers come back as the right kind of address: unnamed when the
+    /// peer never bound, abstract, or a path without the terminating null.
+    pub fn from_accept(storage: &libc::sockaddr_storage,
+                       len: libc::socklen_t) -> Option<SockAddr> {
+        unsafe { super::sockaddr_storage_to_addr(storage, len as usize).ok() }
+    }
+
+    /// Parse the tagged form produced by `format!("{:#}", addr)`, i.e.
+    /// `inet:` followed by any form `InetAddr` parses, or one of the `unix:`
+    /// forms accepted by `UnixAddr::from_tagged_string`.  Other families have
+    /// no tagged form.
+    pub fn from_tagged_string(s: &str) -> Result<SockAddr> {
+        if s.starts_with("inet:") {
+            s["inet:".len()..].parse::<InetAddr>().map(SockAddr::Inet)
+        } else {
+            UnixAddr::from_tagged_string(s).map(SockAddr::Unix)
+        }
+    }
+
+    /// Convert this address into one that owns all of its data.
+    ///
+    /// Every variant is currently plain data, so this is the identity; code
+    /// that needs an owned address should still call it, so it keeps working
+    /// should a variant ever borrow or allocate.
+    pub fn into_owned(self) -> SockAddr {
+        self
+    }
+
+    /// Return a copy of this address with the port changed to `port`.
+    /// Addresses of families without ports are returned unchanged.
+    pub fn with_port(&self, port: u16) -> SockAddr {
+        match *self {
+            SockAddr::Inet(ref inet) => SockAddr::Inet(inet.with_port(port)),
+            other => other,
+        }
+    }
+
+    /// Resolve `host` with the system resolver and return its addresses,
+    /// each with the port of `template`, e.g. to reconnect to a new host on
+    /// the same port.
+    ///
+    /// Fails with `EINVAL` if `template` isn't an internet address, and with
+    /// the resolver's errno, or `ENOENT` if it didn't set one, if `host`
+    /// can't be resolved.
+    pub fn with_resolved_host(host: &str, template: &SockAddr) -> Result<Vec<SockAddr>> {
+        let port = match *template {
+            SockAddr::Inet(ref inet) => inet.port(),
+            _ => return Err(Error::invalid_argument()),
+        };
+        match (host, port).to_socket_addrs() {
+            Ok(addrs) => Ok(addrs.map(|a| SockAddr::Inet(InetAddr::from_std(&a))).collect()),
+            Err(e) => Err(Error::Sys(Errno::from_i32(e.raw_os_error().unwrap_or(libc::ENOENT)))),
+        }
+    }
+
+    /// Return a compact, stable string that identifies this address, for use
+    /// as a key in string-keyed maps such as metric labels.  For example,
+    /// `127.0.0.1:80` yields `inet4:7f000001:0050`.
+    ///
+    /// IPv6 flow information and scope ids are included when nonzero, as in
+    /// `inet6:<address>:<port>/<flowinfo>%<scope id>` with the flow
+    /// information in hex and the scope id in decimal.  Unix addresses use their tagged form (see `UnixAddr::from_tagged_string`),
+    /// so names that aren't valid UTF-8 may collide.
+    pub fn metric_key(&self) -> String {
+        match *self {
+            SockAddr::Inet(InetAddr::V4(ref sa)) => {
+                format!("inet4:{:08x}:{:04x}",
+                        u32::from_be(sa.sin_addr.s_addr),
+                        u16::from_be(sa.sin_port))
+            }
+            SockAddr::Inet(InetAddr::V6(ref sa)) => {
+                let mut key = String::from("inet6:");
+                for b in &sa.sin6_addr.s6_addr {
+                    key.push_str(&format!("{:02x}", b));
+                }
+                key.push_str(&format!(":{:04x}", u16::from_be(sa.sin6_port)));
+                if sa.sin6_flowinfo != 0 {
+                    key.push_str(&format!("/{:08x}", sa.sin6_flowinfo));
+                }
+                if sa.sin6_scope_id != 0 {
+                    key.push_str(&format!("%{}", sa.sin6_scope_id));
+                }
+                key
+            }
+            SockAddr::Unix(ref unix) => format!("{:#}", unix),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Netlink(ref nl) => {
+                format!("netlink:{:08x}:{:08x}", nl.pid(), nl.groups())
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Key(..) => String::from("key:"),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Vsock(ref vsock) => {
+                format!("vsock:{:08x}:{:08x}", vsock.cid(), vsock.port())
+            }
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            SockAddr::Xdp(ref xdp) => {
+                format!("xdp:{:08x}:{:08x}:{:04x}",
+                        xdp.ifindex(), xdp.queue_id(), xdp.flags())
+            }
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            SockAddr::SysControl(ref sc) => {
+                format!("sysctl:{:08x}:{:08x}", sc.id(), sc.unit())
+            }
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            SockAddr::Link(ref link) => {
+                let mut key = format!("link:{:04x}:", link.ifindex());
+                for b in link.addr() {
+                    key.push_str(&format!("{:02x}", b));
+                }
+                key
+            }
+        }
+    }
+
+    /// Return a byte string whose lexicographic order sorts addresses by
+    /// family, then by address and then by port, e.g. for keys of a sorted
+    /// on-disk index.  Two addresses have the same key exactly when they
+    /// compare equal, and comparing keys is how `Ord` is implemented.
+    ///
+    /// The first byte ranks the family, independently of the platform's
+    /// `AF_*` values: 0 for IPv4, 1 for IPv6, 2 for Unix, 3 for netlink, 4
+    /// for `AF_KEY`, 5 for vsock, 6 for XDP, 7 for system control and 8 for
+    /// link-layer addresses.  Then, with integers in big-endian order:
+    ///
+    /// * IPv4: the 4 address bytes and the 16-bit port
+    /// * IPv6: the 16 address bytes, the 16-bit port, the 32-bit scope id
+    ///   and the 32-bit flow info
+    /// * Unix: the raw 16-bit `sun_family`, one byte ranking the kind of
+    ///   address (0 for unnamed, 1 for abstract, 2 for a path) and the raw
+    ///   `sun_path` bytes, so that a path sorts before the paths it is a
+    ///   prefix of
+    /// * netlink: the 32-bit pid and the 32-bit multicast groups
+    /// * `AF_KEY`: nothing
+    /// * vsock: the 32-bit cid and the 32-bit port
+    /// * XDP: the 32-bit interface index, the 32-bit queue id, the 16-bit
+    ///   flags and the 32-bit shared UMEM fd
+    /// * system control: the 32-bit control id and the 32-bit unit
+    /// * link-layer: the length of the hardware address as one byte, the
+    ///   hardware address, the 16-bit interface index, the interface type
+    ///   as one byte and the interface name
+    pub fn sort_key(&self) -> Vec<u8> {
+        let mut key = Vec::with_capacity(32);
+        match *self {
+            SockAddr::Inet(InetAddr::V4(ref sa)) => {
+                key.push(0);
+                key.extend_from_slice(&Ipv4Addr(sa.sin_addr).octets());
+                key.extend_from_slice(&be_u16(u16::from_be(sa.sin_port)));
+            }
+            SockAddr::Inet(InetAddr::V6(ref sa)) => {
+                key.push(1);
+                key.extend_from_slice(&sa.sin6_addr.s6_addr);
+                key.extend_from_slice(&be_u16(u16::from_be(sa.sin6_port)));
+                key.extend_from_slice(&be_u32(sa.sin6_scope_id));
+                key.extend_from_slice(&be_u32(sa.sin6_flowinfo));
+            }
+            SockAddr::Unix(ref unix) => {
+                key.push(2);
+                key.extend_from_slice(&be_u16(unix.0.sun_family as u16));
+                key.push(unix.kind_rank());
+                key.extend_from_slice(unix.sun_path());
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Netlink(ref nl) => {
+                key.push(3);
+                key.extend_from_slice(&be_u32(nl.pid()));
+                key.extend_from_slice(&be_u32(nl.groups()));
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Key(..) => key.push(4),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Vsock(ref vsock) => {
+                key.push(5);
+                key.extend_from_slice(&be_u32(vsock.cid()));
+                key.extend_from_slice(&be_u32(vsock.port()));
+            }
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            SockAddr::Xdp(ref xdp) => {
+                key.push(6);
+                key.extend_from_slice(&be_u32(xdp.ifindex()));
+                key.extend_from_slice(&be_u32(xdp.queue_id()));
+                key.extend_from_slice(&be_u16(xdp.flags()));
+                key.extend_from_slice(&be_u32(xdp.shared_umem_fd() as u32));
+            }
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            SockAddr::SysControl(ref sc) => {
+                key.push(7);
+                key.extend_from_slice(&be_u32(sc.id()));
+                key.extend_from_slice(&be_u32(sc.unit()));
+            }
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            SockAddr::Link(ref link) => {
+                key.push(8);
+                key.push(link.addr().len() as u8);
+                key.extend_from_slice(link.addr());
+                key.extend_from_slice(&be_u16(link.ifindex()));
+                key.push(link.0.sdl_type as u8);
+                key.extend_from_slice(link.name());
+            }
+        }
+        key
+    }
 
-impl fmt::Display for UnixAddr {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.1 == 0 {
-            f.write_str("<unbound UNIX socket>")
-        } else if let Some(path) = self.path() {
-            path.display().fmt(f)
-        } else {
-            let display = String::from_utf8_lossy(&self.sun_path()[1..]);
-            write!(f, "@{}", display)
+    /// Return a byte string that identifies this address: two addresses
+    /// have the same key exactly when they compare equal.  The key consists
+    /// of the address family followed by the address fields in network byte
+    /// order, which makes it a uniform deduplication key across families.
+    /// For Unix addresses the fields are the raw `sun_family`, the kind of
+    /// address and the path, which is everything `==` compares.
+    pub fn identity_key(&self) -> Vec<u8> {
+        let mut key = Vec::with_capacity(32);
+        key.extend_from_slice(&be_u16(self.family() as i32 as u16));
+        match *self {
+            SockAddr::Inet(InetAddr::V4(ref sa)) => {
+                key.extend_from_slice(&Ipv4Addr(sa.sin_addr).octets());
+                key.extend_from_slice(&be_u16(u16::from_be(sa.sin_port)));
+            }
+            SockAddr::Inet(InetAddr::V6(ref sa)) => {
+                key.extend_from_slice(&sa.sin6_addr.s6_addr);
+                key.extend_from_slice(&be_u16(u16::from_be(sa.sin6_port)));
+                key.extend_from_slice(&be_u32(sa.sin6_flowinfo));
+                key.extend_from_slice(&be_u32(sa.sin6_scope_id));
+            }
+            SockAddr::Unix(ref unix) => {
+                key.extend_from_slice(&be_u16(unix.0.sun_family as u16));
+                key.push(unix.kind_rank());
+                key.extend_from_slice(unix.sun_path());
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Netlink(ref nl) => {
+                key.extend_from_slice(&be_u32(nl.pid()));
+                key.extend_from_slice(&be_u32(nl.groups()));
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Key(..) => {}
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Vsock(ref vsock) => {
+                key.extend_from_slice(&be_u32(vsock.cid()));
+                key.extend_from_slice(&be_u32(vsock.port()));
+            }
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            SockAddr::Xdp(ref xdp) => {
+                key.extend_from_slice(&be_u32(xdp.ifindex()));
+                key.extend_from_slice(&be_u32(xdp.queue_id()));
+                key.extend_from_slice(&be_u16(xdp.flags()));
+                key.extend_from_slice(&be_u32(xdp.shared_umem_fd() as u32));
+            }
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            SockAddr::SysControl(ref sc) => {
+                key.extend_from_slice(&be_u32(sc.id()));
+                key.extend_from_slice(&be_u32(sc.unit()));
+            }
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            SockAddr::Link(ref link) => {
+                key.extend_from_slice(&be_u16(link.ifindex()));
+                key.push(link.0.sdl_type as u8);
+                key.push(link.name().len() as u8);
+                key.extend_from_slice(link.name());
+                key.extend_from_slice(link.addr());
+            }
         }
+        key
     }
-}
-
-/*
- *
- * ===== Sock addr =====
- *
- */
 
-/// Represents a socket address
-#[derive(Copy)]
-pub enum SockAddr {
-    Inet(InetAddr),
-    Unix(UnixAddr),
-    #[cfg(any(target_os = "linux", target_os = "android"))]
-    Netlink(NetlinkAddr),
-    #[cfg(any(target_os = "macos", target_os = "ios"))]
-    SysControl(SysControlAddr),
-}
+    /// Return a hash of this address that is stable across processes.
+    ///
+    /// Unlike the `Hash` impl, the result does not depend on the `Hasher` in
+    /// use or on the host byte order: it is a 64-bit FNV-1a hash of
+    /// `identity_key`.  It is therefore suitable as a key in persistent or
+    /// shared maps.
+    pub fn stable_hash(&self) -> u64 {
+        let mut h = Fnv1a::new();
+        h.write(&self.identity_key());
+        h.finish()
+    }
 
-impl SockAddr {
-    pub fn new_inet(addr: InetAddr) -> SockAddr {
-        SockAddr::Inet(addr)
+    /// Whether both addresses refer to the same host: internet addresses
+    /// with the same IP (see `InetAddr::ip_eq`) or Unix addresses that are
+    /// equal.  Addresses of differing families are never the same host.
+    pub fn same_host(&self, other: &SockAddr) -> bool {
+        match (self, other) {
+            (&SockAddr::Inet(ref a), &SockAddr::Inet(ref b)) => a.ip_eq(b),
+            (&SockAddr::Unix(ref a), &SockAddr::Unix(ref b)) => a == b,
+            _ => false,
+        }
     }
 
-    pub fn new_unix<P: ?Sized + NixPath>(path: &P) -> Result<SockAddr> {
-        Ok(SockAddr::Unix(try!(UnixAddr::new(path))))
+    /// Whether this address could be reached from the public internet.
+    ///
+    /// IPv4 addresses qualify unless they are unspecified, loopback,
+    /// private, shared (`100.64.0.0/10`), link-local, IETF protocol
+    /// assignment (`192.0.0.0/24`), documentation, benchmarking, reserved,
+    /// multicast or broadcast addresses.  IPv6
+    /// addresses qualify if they are global unicast (`2000::/3`) and not of
+    /// any `SpecialPurpose`.  Addresses of other families never qualify.
+    pub fn is_publicly_routable(&self) -> bool {
+        match *self {
+            SockAddr::Inet(InetAddr::V4(ref sa)) => {
+                let ip = Ipv4Addr(sa.sin_addr).to_std();
+                let o = ip.octets();
+                !(o[0] == 0 || ip.is_loopback() || ip.is_private() ||
+                  (o[0] == 100 && o[1] & 0xc0 == 64) || ip.is_link_local() ||
+                  (o[0] == 192 && o[1] == 0 && o[2] == 0) ||
+                  ip.is_documentation() || (o[0] == 198 && o[1] & 0xfe == 18) ||
+                  o[0] >= 224)
+            }
+            SockAddr::Inet(InetAddr::V6(ref sa)) => {
+                let ip = Ipv6Addr(sa.sin6_addr);
+                ip.octets()[0] & 0xe0 == 0x20 && ip.special_purpose().is_none()
+            }
+            _ => false,
+        }
     }
 
-    #[cfg(any(target_os = "linux", target_os = "android"))]
-    pub fn new_netlink(pid: u32, groups: u32) -> SockAddr {
-        SockAddr::Netlink(NetlinkAddr::new(pid, groups))
+    /// Return a copy of this address with identifying parts removed, for
+    /// privacy-preserving logs: IPv4 addresses keep their `/24` network and
+    /// IPv6 addresses their `/48` network, with the flow label cleared; ports
+    /// are kept.  Unix paths are reduced to their last component, or to an
+    /// unnamed address if that component can't be made into an address of
+    /// its own.  Other addresses are returned unchanged.
+    pub fn anonymized(&self) -> SockAddr {
+        match *self {
+            SockAddr::Inet(InetAddr::V4(mut sa)) => {
+                sa.sin_addr.s_addr &= v4_mask(24).to_be();
+                SockAddr::Inet(InetAddr::V4(sa))
+            }
+            SockAddr::Inet(InetAddr::V6(mut sa)) => {
+                sa.sin6_addr = Ipv6Addr(sa.sin6_addr).network(48).0;
+                sa.sin6_flowinfo = 0;
+                SockAddr::Inet(InetAddr::V6(sa))
+            }
+            SockAddr::Unix(ref unix) => {
+                match unix.path().and_then(Path::file_name) {
+                    Some(name) => match UnixAddr::new(name) {
+                        Ok(anon) => SockAddr::Unix(anon),
+                        Err(_) => {
+                            let mut sa: libc::sockaddr_un = unsafe { mem::zeroed() };
+                            sa.sun_family = unix.0.sun_family;
+                            SockAddr::Unix(UnixAddr(sa, 0))
+                        }
+                    },
+                    None => *self,
+                }
+            }
+            _ => *self,
+        }
     }
 
-    #[cfg(any(target_os = "macos", target_os = "ios"))]
-    pub fn new_sys_control(sockfd: RawFd, name: &str, unit: u32) -> Result<SockAddr> {
-        SysControlAddr::from_name(sockfd, name, unit).map(|a| SockAddr::SysControl(a))
+    /// Whether this address makes sense as an argument to `bind`.  Internet
+    /// addresses do, including the wildcard address and port 0, except for
+    /// the IPv4 limited broadcast address `255.255.255.255`.  Unnamed Unix
+    /// addresses don't; other addresses do.
+    ///
+    /// This only catches configuration mistakes; `bind` may still fail, e.g.
+    /// if the IP isn't local.
+    pub fn is_bindable(&self) -> bool {
+        match *self {
+            SockAddr::Inet(InetAddr::V4(ref sa)) => sa.sin_addr.s_addr != !0,
+            SockAddr::Inet(InetAddr::V6(..)) => true,
+            SockAddr::Unix(ref unix) => !unix.is_unnamed(),
+            _ => true,
+        }
     }
 
-    pub fn family(&self) -> AddressFamily {
+    /// Whether this address makes sense as an argument to `connect`.
+    /// Internet addresses don't if they are the wildcard address or have
+    /// port 0, and unnamed Unix addresses don't either; other addresses do.
+    pub fn is_connectable(&self) -> bool {
         match *self {
-            SockAddr::Inet(InetAddr::V4(..)) => AddressFamily::Inet,
-            SockAddr::Inet(InetAddr::V6(..)) => AddressFamily::Inet6,
-            SockAddr::Unix(..) => AddressFamily::Unix,
-            #[cfg(any(target_os = "linux", target_os = "android"))]
-            SockAddr::Netlink(..) => AddressFamily::Netlink,
-            #[cfg(any(target_os = "macos", target_os = "ios"))]
-            SockAddr::SysControl(..) => AddressFamily::System,
+            SockAddr::Inet(ref inet) => !inet.is_wildcard() && inet.port() != 0,
+            SockAddr::Unix(ref unix) => !unix.is_unnamed(),
+            _ => true,
         }
     }
 
-    pub fn to_str(&self) -> String {
-        format!("{}", self)
+    /// Whether binding one socket to `self` and another to `other` would
+    /// fail with `EADDRINUSE`, assuming neither uses `SO_REUSEADDR` or
+    /// `SO_REUSEPORT` and IPv6 sockets are dual-stack (`IPV6_V6ONLY` off).
+    ///
+    /// Internet addresses conflict if they have the same nonzero port and
+    /// either the same IP, or one of them is the wildcard address of a family
+    /// that covers the other; `::` covers IPv4 as well.  IPv4-mapped IPv6
+    /// addresses count as IPv4.  Named Unix addresses conflict if they are
+    /// equal, unnamed ones never do.  Other addresses conflict if equal.
+    pub fn conflicts_with(&self, other: &SockAddr) -> bool {
+        match (self, other) {
+            (&SockAddr::Inet(ref a), &SockAddr::Inet(ref b)) => inet_bind_conflict(a, b),
+            (&SockAddr::Unix(ref a), &SockAddr::Unix(ref b)) => a.1 > 0 && a == b,
+            _ => self == other,
+        }
     }
 
     pub unsafe fn as_ffi_pair(&self) -> (&libc::sockaddr, libc::socklen_t) {
         match *self {
-            SockAddr::Inet(InetAddr::V4(ref addr)) => (mem::transmute(addr), mem::size_of::<libc::sockaddr_in>() as libc::socklen_t),
-            SockAddr::Inet(InetAddr::V6(ref addr)) => (mem::transmute(addr), mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t),
-            SockAddr::Unix(UnixAddr(ref addr, len)) => (mem::transmute(addr), (len + offset_of!(libc::sockaddr_un, sun_path)) as libc::socklen_t),
+            SockAddr::Inet(ref inet) => inet.as_ffi_pair(),
+            SockAddr::Unix(ref unix) => (mem::transmute(&unix.0), unix.ffi_len()),
             #[cfg(any(target_os = "linux", target_os = "android"))]
             SockAddr::Netlink(NetlinkAddr(ref sa)) => (mem::transmute(sa), mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Key(KeyAddr(ref sa)) => (sa, mem::size_of::<libc::sockaddr>() as libc::socklen_t),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Vsock(VsockAddr(ref sa)) => (mem::transmute(sa), mem::size_of::<libc::sockaddr_vm>() as libc::socklen_t),
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            SockAddr::Xdp(XdpAddr(ref sa)) => (mem::transmute(sa), mem::size_of::<libc::sockaddr_xdp>() as libc::socklen_t),
             #[cfg(any(target_os = "macos", target_os = "ios"))]
             SockAddr::SysControl(SysControlAddr(ref sa)) => (mem::transmute(sa), mem::size_of::<sys_control::sockaddr_ctl>() as libc::socklen_t),
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            SockAddr::Link(LinkAddr(ref sa)) => (mem::transmute(sa), mem::size_of::<libc::sockaddr_dl>() as libc::socklen_t),
         }
     }
 }
@@ -547,6 +2764,27 @@ impl PartialEq for SockAddr {
             (SockAddr::Netlink(ref a), SockAddr::Netlink(ref b)) => {
                 a == b
             }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (SockAddr::Key(ref a), SockAddr::Key(ref b)) => {
+                a == b
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (SockAddr::Vsock(ref a), SockAddr::Vsock(ref b)) => {
+                a == b
+            }
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            (SockAddr::Xdp(ref a), SockAddr::Xdp(ref b)) => {
+                a == b
+            }
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            (SockAddr::Link(ref a), SockAddr::Link(ref b)) => {
+                a == b
+            }
             _ => false,
         }
     }
@@ -555,6 +2793,18 @@ impl PartialEq for SockAddr {
 impl Eq for SockAddr {
 }
 
+impl PartialOrd for SockAddr {
+    fn partial_cmp(&self, other: &SockAddr) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SockAddr {
+    fn cmp(&self, other: &SockAddr) -> cmp::Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
 impl hash::Hash for SockAddr {
     fn hash<H: hash::Hasher>(&self, s: &mut H) {
         match *self {
@@ -562,9 +2812,47 @@ impl hash::Hash for SockAddr {
             SockAddr::Unix(ref a) => a.hash(s),
             #[cfg(any(target_os = "linux", target_os = "android"))]
             SockAddr::Netlink(ref a) => a.hash(s),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Key(ref a) => a.hash(s),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Vsock(ref a) => a.hash(s),
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            SockAddr::Xdp(ref a) => a.hash(s),
             #[cfg(any(target_os = "macos", target_os = "ios"))]
             SockAddr::SysControl(ref a) => a.hash(s),
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            SockAddr::Link(ref a) => a.hash(s),
+        }
+    }
+}
+
+impl FromStr for SockAddr {
+    type Err = Error;
+
+    /// Parse an internet address as `InetAddr` does, falling back to a Unix
+    /// address otherwise: `@name` is a name in the Linux abstract namespace
+    /// and anything else a filesystem path.  Fails with `ENAMETOOLONG` if
+    /// the name doesn't fit into `sun_path`, and with `EINVAL` for an empty
+    /// string.
+    fn from_str(s: &str) -> Result<SockAddr> {
+        if s.is_empty() {
+            return Err(Error::invalid_argument());
         }
+        if let Ok(inet) = s.parse::<InetAddr>() {
+            return Ok(SockAddr::Inet(inet));
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            if s.starts_with('@') {
+                return UnixAddr::new_abstract(s[1..].as_bytes()).map(SockAddr::Unix);
+            }
+        }
+        UnixAddr::new(s).map(SockAddr::Unix)
     }
 }
 
@@ -581,10 +2869,52 @@ impl fmt::Display for SockAddr {
             SockAddr::Unix(ref unix) => unix.fmt(f),
             #[cfg(any(target_os = "linux", target_os = "android"))]
             SockAddr::Netlink(ref nl) => nl.fmt(f),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Key(ref key) => key.fmt(f),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Vsock(ref vsock) => vsock.fmt(f),
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            SockAddr::Xdp(ref xdp) => xdp.fmt(f),
             #[cfg(any(target_os = "macos", target_os = "ios"))]
             SockAddr::SysControl(ref sc) => sc.fmt(f),
+            #[cfg(any(target_os = "macos",
+                      target_os = "ios",
+                      target_os = "freebsd",
+                      target_os = "dragonfly",
+                      target_os = "openbsd",
+                      target_os = "netbsd"))]
+            SockAddr::Link(ref link) => link.fmt(f),
+        }
+    }
+}
+
+/// 64-bit FNV-1a.  Used where a hash must not depend on the platform's
+/// `Hasher` implementation.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
         }
     }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn be_u16(v: u16) -> [u8; 2] {
+    [(v >> 8) as u8, v as u8]
+}
+
+fn be_u32(v: u32) -> [u8; 4] {
+    [(v >> 24) as u8, (v >> 16) as u8, (v >> 8) as u8, v as u8]
 }
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
@@ -642,6 +2972,176 @@ pub mod netlink {
     }
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod key {
+    use ::sys::socket::addr::AddressFamily;
+    use libc::{sa_family_t, sockaddr};
+    use std::{fmt, mem};
+    use std::hash::{Hash, Hasher};
+
+    /// The address of a `PF_KEY` (RFC 2367) key management socket.  Such
+    /// sockets talk to the kernel only, so the address carries nothing but
+    /// the family.
+    #[derive(Copy, Clone)]
+    pub struct KeyAddr(pub sockaddr);
+
+    impl PartialEq for KeyAddr {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.sa_family == other.0.sa_family
+        }
+    }
+
+    impl Eq for KeyAddr {}
+
+    impl Hash for KeyAddr {
+        fn hash<H: Hasher>(&self, s: &mut H) {
+            self.0.sa_family.hash(s);
+        }
+    }
+
+    impl KeyAddr {
+        pub fn new() -> KeyAddr {
+            let mut addr: sockaddr = unsafe { mem::zeroed() };
+            addr.sa_family = AddressFamily::Key as sa_family_t;
+
+            KeyAddr(addr)
+        }
+    }
+
+    impl fmt::Display for KeyAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("pf_key")
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod vsock {
+    use ::sys::socket::addr::AddressFamily;
+    use libc::{sa_family_t, sockaddr_vm};
+    use std::{fmt, mem};
+    use std::hash::{Hash, Hasher};
+
+    #[derive(Copy, Clone)]
+    pub struct VsockAddr(pub sockaddr_vm);
+
+    impl PartialEq for VsockAddr {
+        fn eq(&self, other: &Self) -> bool {
+            let (inner, other) = (self.0, other.0);
+            (inner.svm_family, inner.svm_cid, inner.svm_port) ==
+            (other.svm_family, other.svm_cid, other.svm_port)
+        }
+    }
+
+    impl Eq for VsockAddr {}
+
+    impl Hash for VsockAddr {
+        fn hash<H: Hasher>(&self, s: &mut H) {
+            let inner = self.0;
+            (inner.svm_family, inner.svm_cid, inner.svm_port).hash(s);
+        }
+    }
+
+    impl VsockAddr {
+        /// Create an address for `port` on the virtual machine with context
+        /// id `cid`.  `libc::VMADDR_CID_ANY` and `libc::VMADDR_PORT_ANY`
+        /// act as wildcards when binding.
+        pub fn new(cid: u32, port: u32) -> VsockAddr {
+            let mut addr: sockaddr_vm = unsafe { mem::zeroed() };
+            addr.svm_family = AddressFamily::Vsock as sa_family_t;
+            addr.svm_cid = cid;
+            addr.svm_port = port;
+
+            VsockAddr(addr)
+        }
+
+        pub fn cid(&self) -> u32 {
+            self.0.svm_cid
+        }
+
+        pub fn port(&self) -> u32 {
+            self.0.svm_port
+        }
+    }
+
+    impl fmt::Display for VsockAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "cid: {} port: {}", self.cid(), self.port())
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+pub mod xdp {
+    use ::sys::socket::addr::AddressFamily;
+    use libc::{sa_family_t, sockaddr_xdp};
+    use std::{fmt, mem};
+    use std::hash::{Hash, Hasher};
+    use std::os::unix::io::RawFd;
+
+    /// An `AF_XDP` socket address, which binds an XDP socket to one queue of
+    /// a network interface
+    #[derive(Copy, Clone)]
+    pub struct XdpAddr(pub sockaddr_xdp);
+
+    impl PartialEq for XdpAddr {
+        fn eq(&self, other: &Self) -> bool {
+            let (inner, other) = (self.0, other.0);
+            (inner.sxdp_flags, inner.sxdp_ifindex, inner.sxdp_queue_id, inner.sxdp_shared_umem_fd) ==
+            (other.sxdp_flags, other.sxdp_ifindex, other.sxdp_queue_id, other.sxdp_shared_umem_fd)
+        }
+    }
+
+    impl Eq for XdpAddr {}
+
+    impl Hash for XdpAddr {
+        fn hash<H: Hasher>(&self, s: &mut H) {
+            let inner = self.0;
+            (inner.sxdp_flags, inner.sxdp_ifindex, inner.sxdp_queue_id, inner.sxdp_shared_umem_fd).hash(s);
+        }
+    }
+
+    impl XdpAddr {
+        /// Create an address for queue `queue_id` of the interface with index
+        /// `ifindex`.  `flags` is a combination of the `XDP_*` bind flags,
+        /// such as `libc::XDP_ZEROCOPY`.  `shared_umem_fd` is the XDP socket
+        /// whose UMEM should be shared; it is only used when `flags` contains
+        /// `libc::XDP_SHARED_UMEM`.
+        pub fn new(ifindex: u32, queue_id: u32, flags: u16, shared_umem_fd: RawFd) -> XdpAddr {
+            let mut addr: sockaddr_xdp = unsafe { mem::zeroed() };
+            addr.sxdp_family = AddressFamily::Xdp as sa_family_t;
+            addr.sxdp_flags = flags;
+            addr.sxdp_ifindex = ifindex;
+            addr.sxdp_queue_id = queue_id;
+            addr.sxdp_shared_umem_fd = shared_umem_fd as u32;
+
+            XdpAddr(addr)
+        }
+
+        pub fn ifindex(&self) -> u32 {
+            self.0.sxdp_ifindex
+        }
+
+        pub fn queue_id(&self) -> u32 {
+            self.0.sxdp_queue_id
+        }
+
+        pub fn flags(&self) -> u16 {
+            self.0.sxdp_flags
+        }
+
+        pub fn shared_umem_fd(&self) -> RawFd {
+            self.0.sxdp_shared_umem_fd as RawFd
+        }
+    }
+
+    impl fmt::Display for XdpAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "ifindex: {} queue: {}", self.ifindex(), self.queue_id())
+        }
+    }
+}
+
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 pub mod sys_control {
     use ::sys::socket::consts;
@@ -740,3 +3240,102 @@ pub mod sys_control {
         }
     }
 }
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "openbsd",
+          target_os = "netbsd"))]
+pub mod link {
+    use ::sys::socket::addr::AddressFamily;
+    use libc::{c_uchar, sockaddr_dl};
+    use std::{fmt, mem, slice};
+    use std::hash::{Hash, Hasher};
+    use {Errno, Error, Result};
+
+    /// A link-layer address (`AF_LINK`), as used for interface operations on
+    /// BSD-derived systems.
+    #[derive(Copy, Clone)]
+    pub struct LinkAddr(pub sockaddr_dl);
+
+    impl PartialEq for LinkAddr {
+        fn eq(&self, other: &Self) -> bool {
+            (self.0.sdl_index, self.0.sdl_type, self.name(), self.addr()) ==
+            (other.0.sdl_index, other.0.sdl_type, other.name(), other.addr())
+        }
+    }
+
+    impl Eq for LinkAddr {}
+
+    impl Hash for LinkAddr {
+        fn hash<H: Hasher>(&self, s: &mut H) {
+            (self.0.sdl_index, self.0.sdl_type, self.name(), self.addr()).hash(s);
+        }
+    }
+
+    impl LinkAddr {
+        /// Create a link-layer address for the interface with index
+        /// `ifindex` and hardware address `addr`.  Fails with `ENAMETOOLONG`
+        /// if `addr` doesn't fit in `sdl_data`.
+        pub fn new(ifindex: u16, addr: &[u8]) -> Result<LinkAddr> {
+            let mut sdl: sockaddr_dl = unsafe { mem::zeroed() };
+
+            if addr.len() > sdl.sdl_data.len() {
+                return Err(Error::Sys(Errno::ENAMETOOLONG));
+            }
+
+            sdl.sdl_len = mem::size_of::<sockaddr_dl>() as c_uchar;
+            sdl.sdl_family = AddressFamily::Link as c_uchar;
+            sdl.sdl_index = ifindex;
+            sdl.sdl_alen = addr.len() as c_uchar;
+            for (dst, src) in sdl.sdl_data.iter_mut().zip(addr) {
+                *dst = *src as _;
+            }
+
+            Ok(LinkAddr(sdl))
+        }
+
+        /// The index of the interface this address belongs to
+        pub fn ifindex(&self) -> u16 {
+            self.0.sdl_index
+        }
+
+        /// The interface name, if the kernel filled it in
+        pub fn name(&self) -> &[u8] {
+            self.data(0, self.0.sdl_nlen as usize)
+        }
+
+        /// The hardware address, e.g. a 6-byte MAC for Ethernet interfaces
+        pub fn addr(&self) -> &[u8] {
+            self.data(self.0.sdl_nlen as usize, self.0.sdl_alen as usize)
+        }
+
+        // `sdl_data` holds the interface name immediately followed by the
+        // hardware address.  Clamp to the array in case the lengths are
+        // bogus.
+        fn data(&self, start: usize, len: usize) -> &[u8] {
+            let size = self.0.sdl_data.len();
+            let start = if start > size { size } else { start };
+            let len = if len > size - start { size - start } else { len };
+            unsafe {
+                slice::from_raw_parts(
+                    self.0.sdl_data.as_ptr().offset(start as isize) as *const u8,
+                    len)
+            }
+        }
+    }
+
+    impl fmt::Display for LinkAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            try!(write!(f, "index: {} addr: ", self.ifindex()));
+            for (i, b) in self.addr().iter().enumerate() {
+                if i > 0 {
+                    try!(f.write_str(":"));
+                }
+                try!(write!(f, "{:02x}", b));
+            }
+            Ok(())
+        }
+    }
+}
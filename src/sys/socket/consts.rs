@@ -10,6 +10,10 @@ mod os {
     pub const AF_INET6: c_int = libc::AF_INET6;
     pub const AF_NETLINK: c_int = libc::AF_NETLINK;
     pub const AF_PACKET: c_int = libc::AF_PACKET;
+    pub const AF_KEY: c_int = libc::AF_KEY;
+    pub const AF_VSOCK: c_int = libc::AF_VSOCK;
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    pub const AF_XDP: c_int = libc::AF_XDP;
 
     pub const SOCK_STREAM: c_int = libc::SOCK_STREAM;
     pub const SOCK_DGRAM: c_int = libc::SOCK_DGRAM;
@@ -125,6 +129,7 @@ mod os {
     pub const AF_LOCAL: c_int = libc::AF_LOCAL;
     pub const AF_INET: c_int  = libc::AF_INET;
     pub const AF_INET6: c_int = libc::AF_INET6;
+    pub const AF_LINK: c_int = libc::AF_LINK;
     #[cfg(any(target_os = "macos", target_os = "ios"))]
     pub const AF_SYSTEM: c_int = libc::AF_SYSTEM;
 
@@ -245,6 +250,7 @@ mod os {
     pub const AF_LOCAL: c_int = libc::AF_LOCAL;
     pub const AF_INET: c_int  = libc::AF_INET;
     pub const AF_INET6: c_int = libc::AF_INET6;
+    pub const AF_LINK: c_int = libc::AF_LINK;
 
     pub const SOCK_STREAM: c_int = libc::SOCK_STREAM;
     pub const SOCK_DGRAM: c_int = libc::SOCK_DGRAM;
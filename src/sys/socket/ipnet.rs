@@ -0,0 +1,85 @@
+//! Conversions between nix's address types and the networks of `ipnet`
+use ::ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use {Error, Result};
+use super::{Cidr, CidrV4, CidrV6, IpAddr, Ipv4Addr, Ipv6Addr};
+
+impl Ipv4Addr {
+    /// Return the `ipnet` network of this address with a prefix of
+    /// `prefix_len` bits.  Host bits are kept, as by `CidrV4`.  Fails with
+    /// `EINVAL` if `prefix_len` is greater than 32.
+    pub fn to_ipv4_net(&self, prefix_len: u8) -> Result<Ipv4Net> {
+        CidrV4::new(*self, prefix_len).and_then(|cidr| cidr.to_ipv4_net())
+    }
+}
+
+impl Ipv6Addr {
+    /// Return the `ipnet` network of this address with a prefix of
+    /// `prefix_len` bits.  Host bits are kept, as by `CidrV6`.  Fails with
+    /// `EINVAL` if `prefix_len` is greater than 128.
+    pub fn to_ipv6_net(&self, prefix_len: u8) -> Result<Ipv6Net> {
+        CidrV6::new(*self, prefix_len).and_then(|cidr| cidr.to_ipv6_net())
+    }
+}
+
+impl IpAddr {
+    /// Return the `ipnet` network of this address with a prefix of
+    /// `prefix_len` bits.  Fails with `EINVAL` if `prefix_len` exceeds the
+    /// width of the address.
+    pub fn to_ip_net(&self, prefix_len: u8) -> Result<IpNet> {
+        match *self {
+            IpAddr::V4(ref ip) => ip.to_ipv4_net(prefix_len).map(IpNet::V4),
+            IpAddr::V6(ref ip) => ip.to_ipv6_net(prefix_len).map(IpNet::V6),
+        }
+    }
+}
+
+impl CidrV4 {
+    /// Return this network as an `ipnet` network.  Fails with `EINVAL` if
+    /// `ipnet` rejects the prefix length.
+    pub fn to_ipv4_net(&self) -> Result<Ipv4Net> {
+        Ipv4Net::new(self.addr().to_std(), self.prefix_len())
+            .map_err(|_| Error::invalid_argument())
+    }
+
+    /// Create a `CidrV4` from an `ipnet` network, keeping its host bits.
+    /// Fails with `EINVAL` if the prefix length is greater than 32.
+    pub fn from_ipv4_net(net: &Ipv4Net) -> Result<CidrV4> {
+        CidrV4::new(Ipv4Addr::from_std(&net.addr()), net.prefix_len())
+    }
+}
+
+impl CidrV6 {
+    /// Return this network as an `ipnet` network.  Fails with `EINVAL` if
+    /// `ipnet` rejects the prefix length.
+    pub fn to_ipv6_net(&self) -> Result<Ipv6Net> {
+        Ipv6Net::new(self.addr().to_std(), self.prefix_len())
+            .map_err(|_| Error::invalid_argument())
+    }
+
+    /// Create a `CidrV6` from an `ipnet` network, keeping its host bits.
+    /// Fails with `EINVAL` if the prefix length is greater than 128.
+    pub fn from_ipv6_net(net: &Ipv6Net) -> Result<CidrV6> {
+        CidrV6::new(Ipv6Addr::from_std(&net.addr()), net.prefix_len())
+    }
+}
+
+impl Cidr {
+    /// Return this network as an `ipnet` network.  Fails with `EINVAL` if
+    /// `ipnet` rejects the prefix length.
+    pub fn to_ip_net(&self) -> Result<IpNet> {
+        match *self {
+            Cidr::V4(ref cidr) => cidr.to_ipv4_net().map(IpNet::V4),
+            Cidr::V6(ref cidr) => cidr.to_ipv6_net().map(IpNet::V6),
+        }
+    }
+
+    /// Create a `Cidr` from an `ipnet` network, keeping its host bits.
+    /// Fails with `EINVAL` if the prefix length exceeds the width of the
+    /// address.
+    pub fn from_ip_net(net: &IpNet) -> Result<Cidr> {
+        match *net {
+            IpNet::V4(ref net) => CidrV4::from_ipv4_net(net).map(Cidr::V4),
+            IpNet::V6(ref net) => CidrV6::from_ipv6_net(net).map(Cidr::V6),
+        }
+    }
+}
@@ -15,6 +15,12 @@ mod consts;
 mod ffi;
 mod multicast;
 pub mod sockopt;
+#[cfg(feature = "smoltcp")]
+mod smoltcp;
+#[cfg(feature = "ipnet")]
+mod ipnet;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 /*
  *
@@ -24,15 +30,35 @@ pub mod sockopt;
 
 pub use self::addr::{
     AddressFamily,
+    Transport,
     SockAddr,
     InetAddr,
     UnixAddr,
     IpAddr,
     Ipv4Addr,
     Ipv6Addr,
+    SpecialPurpose,
+    Cidr,
+    CidrV4,
+    CidrV6,
+    flow_hash,
+    MAX_ABSTRACT_LEN,
 };
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub use ::sys::socket::addr::netlink::NetlinkAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use ::sys::socket::addr::key::KeyAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use ::sys::socket::addr::vsock::VsockAddr;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+pub use ::sys::socket::addr::xdp::XdpAddr;
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "openbsd",
+          target_os = "netbsd"))]
+pub use ::sys::socket::addr::link::LinkAddr;
 
 pub use libc::{
     in_addr,
@@ -625,7 +651,14 @@ pub fn getsockname(fd: RawFd) -> Result<SockAddr> {
 /// should be the number of bytes in the sockaddr_storage that are actually
 /// allocated and valid.  It must be at least as large as all the useful parts
 /// of the structure.  Note that in the case of a `sockaddr_un`, `len` need not
-/// include the terminating null.
+/// include the terminating null, and that a `len` covering just the family
+/// denotes an unnamed socket, as `accept` reports for unbound peers.
+///
+/// Fails with `EINVAL` if `len` doesn't fit the address family, and with
+/// `EAFNOSUPPORT` if the family isn't supported.
+///
+/// The returned `SockAddr` holds a copy of the address, so `addr` may be
+/// freed or reused afterwards.
 pub unsafe fn sockaddr_storage_to_addr(
     addr: &sockaddr_storage,
     len: usize) -> Result<SockAddr> {
@@ -636,25 +669,74 @@ pub unsafe fn sockaddr_storage_to_addr(
 
     match addr.ss_family as c_int {
         consts::AF_INET => {
-            assert!(len as usize == mem::size_of::<sockaddr_in>());
+            if len != mem::size_of::<sockaddr_in>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
             let ret = *(addr as *const _ as *const sockaddr_in);
             Ok(SockAddr::Inet(InetAddr::V4(ret)))
         }
         consts::AF_INET6 => {
-            assert!(len as usize == mem::size_of::<sockaddr_in6>());
+            if len != mem::size_of::<sockaddr_in6>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
             Ok(SockAddr::Inet(InetAddr::V6((*(addr as *const _ as *const sockaddr_in6)))))
         }
         consts::AF_UNIX => {
             let sun = *(addr as *const _ as *const sockaddr_un);
-            let pathlen = len - offset_of!(sockaddr_un, sun_path);
-            Ok(SockAddr::Unix(UnixAddr(sun, pathlen)))
+            let offset = offset_of!(sockaddr_un, sun_path);
+            if len < offset || len > mem::size_of::<sockaddr_un>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
+            let mut path_len = len - offset;
+            // For pathname sockets the kernel may count the terminating
+            // null; drop it so the result equals `UnixAddr::new(path)`.
+            // Abstract names start with a null and are taken verbatim.
+            if path_len > 0 && sun.sun_path[0] != 0 {
+                while path_len > 0 && sun.sun_path[path_len - 1] == 0 {
+                    path_len -= 1;
+                }
+            }
+            Ok(SockAddr::Unix(UnixAddr(sun, path_len)))
         }
         #[cfg(any(target_os = "linux", target_os = "android"))]
         consts::AF_NETLINK => {
             use libc::sockaddr_nl;
             Ok(SockAddr::Netlink(NetlinkAddr(*(addr as *const _ as *const sockaddr_nl))))
         }
-        af => panic!("unexpected address family {}", af),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        consts::AF_KEY => {
+            if len < mem::size_of::<sockaddr>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
+            Ok(SockAddr::Key(KeyAddr(*(addr as *const _ as *const sockaddr))))
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        consts::AF_VSOCK => {
+            use libc::sockaddr_vm;
+            if len != mem::size_of::<sockaddr_vm>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
+            Ok(SockAddr::Vsock(VsockAddr(*(addr as *const _ as *const sockaddr_vm))))
+        }
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        consts::AF_XDP => {
+            use libc::sockaddr_xdp;
+            if len != mem::size_of::<sockaddr_xdp>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
+            Ok(SockAddr::Xdp(XdpAddr(*(addr as *const _ as *const sockaddr_xdp))))
+        }
+        #[cfg(any(target_os = "macos",
+                  target_os = "ios",
+                  target_os = "freebsd",
+                  target_os = "dragonfly",
+                  target_os = "openbsd",
+                  target_os = "netbsd"))]
+        consts::AF_LINK => {
+            use libc::sockaddr_dl;
+            Ok(SockAddr::Link(LinkAddr(*(addr as *const _ as *const sockaddr_dl))))
+        }
+        _ => Err(Error::Sys(Errno::EAFNOSUPPORT)),
     }
 }
 
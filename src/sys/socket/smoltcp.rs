@@ -0,0 +1,60 @@
+//! Conversions between nix's address types and those of `smoltcp`
+use ::smoltcp::wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv6Address};
+use std::net;
+use super::{InetAddr, IpAddr, Ipv4Addr, Ipv6Addr};
+
+impl From<Ipv4Addr> for Ipv4Address {
+    fn from(addr: Ipv4Addr) -> Ipv4Address {
+        Ipv4Address(addr.octets())
+    }
+}
+
+impl From<Ipv4Address> for Ipv4Addr {
+    fn from(addr: Ipv4Address) -> Ipv4Addr {
+        let o = addr.0;
+        Ipv4Addr::new(o[0], o[1], o[2], o[3])
+    }
+}
+
+impl From<Ipv6Addr> for Ipv6Address {
+    fn from(addr: Ipv6Addr) -> Ipv6Address {
+        Ipv6Address(addr.octets())
+    }
+}
+
+impl From<Ipv6Address> for Ipv6Addr {
+    fn from(addr: Ipv6Address) -> Ipv6Addr {
+        Ipv6Addr::from_std(&net::Ipv6Addr::from(addr.0))
+    }
+}
+
+impl From<IpAddr> for IpAddress {
+    fn from(addr: IpAddr) -> IpAddress {
+        match addr {
+            IpAddr::V4(ip) => IpAddress::Ipv4(ip.into()),
+            IpAddr::V6(ip) => IpAddress::Ipv6(ip.into()),
+        }
+    }
+}
+
+impl From<IpAddress> for IpAddr {
+    fn from(addr: IpAddress) -> IpAddr {
+        match addr {
+            IpAddress::Ipv4(ip) => IpAddr::V4(ip.into()),
+            IpAddress::Ipv6(ip) => IpAddr::V6(ip.into()),
+        }
+    }
+}
+
+/// The IPv6 flow label and scope id have no smoltcp equivalent and are lost.
+impl From<InetAddr> for IpEndpoint {
+    fn from(addr: InetAddr) -> IpEndpoint {
+        IpEndpoint::new(addr.ip().into(), addr.port())
+    }
+}
+
+impl From<IpEndpoint> for InetAddr {
+    fn from(endpoint: IpEndpoint) -> InetAddr {
+        InetAddr::new(endpoint.addr.into(), endpoint.port)
+    }
+}
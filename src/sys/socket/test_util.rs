@@ -0,0 +1,115 @@
+//! Helpers for checking that address types behave consistently
+//!
+//! Downstream crates that wrap nix's address types can run their addresses
+//! through `assert_addr_invariants` to make sure equality, hashing and the
+//! conversions to and from `std::net` agree with each other.
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io, net};
+use std::os::unix::fs::DirBuilderExt;
+use libc;
+use {Errno, Error, Result};
+use super::{InetAddr, Ipv4Addr, Ipv6Addr};
+
+/// Address types that convert losslessly to and from a `std::net` type
+pub trait StdAddr: Sized {
+    type Std;
+
+    fn to_std(&self) -> Self::Std;
+
+    fn from_std(std: &Self::Std) -> Self;
+}
+
+impl StdAddr for InetAddr {
+    type Std = net::SocketAddr;
+
+    fn to_std(&self) -> net::SocketAddr {
+        InetAddr::to_std(self)
+    }
+
+    fn from_std(std: &net::SocketAddr) -> InetAddr {
+        InetAddr::from_std(std)
+    }
+}
+
+impl StdAddr for Ipv4Addr {
+    type Std = net::Ipv4Addr;
+
+    fn to_std(&self) -> net::Ipv4Addr {
+        Ipv4Addr::to_std(self)
+    }
+
+    fn from_std(std: &net::Ipv4Addr) -> Ipv4Addr {
+        Ipv4Addr::from_std(std)
+    }
+}
+
+impl StdAddr for Ipv6Addr {
+    type Std = net::Ipv6Addr;
+
+    fn to_std(&self) -> net::Ipv6Addr {
+        Ipv6Addr::to_std(self)
+    }
+
+    fn from_std(std: &net::Ipv6Addr) -> Ipv6Addr {
+        Ipv6Addr::from_std(std)
+    }
+}
+
+fn hash_of<A: Hash>(addr: &A) -> u64 {
+    let mut s = DefaultHasher::new();
+    addr.hash(&mut s);
+    s.finish()
+}
+
+/// Panic unless `addr` equals itself, survives a round trip through its
+/// `std::net` counterpart, and hashes the same as that round-tripped copy.
+pub fn assert_addr_invariants<A: Eq + Hash + StdAddr>(addr: &A) {
+    assert!(addr == addr, "address is not equal to itself");
+
+    let round_trip = A::from_std(&addr.to_std());
+    assert!(round_trip == *addr, "address changed in a round trip through std");
+    assert_eq!(hash_of(&round_trip), hash_of(addr),
+               "equal addresses hash differently");
+}
+
+/// A private temporary directory for socket files, created by
+/// `UnixAddr::pair_in_tempdir` and removed with its contents on drop
+#[derive(Debug)]
+pub struct TempSocketDir {
+    path: PathBuf,
+}
+
+impl TempSocketDir {
+    /// Create a new, uniquely named directory accessible only to the owner
+    pub fn new() -> Result<TempSocketDir> {
+        let pid = unsafe { libc::getpid() };
+        let mut n = 0;
+        loop {
+            let path = env::temp_dir().join(format!("nix-sock-{}-{}", pid, n));
+            n += 1;
+            // Skip over directories that already exist, whether created by
+            // another thread or left behind by an earlier process with the
+            // same pid
+            match fs::DirBuilder::new().mode(0o700).create(&path) {
+                Ok(()) => return Ok(TempSocketDir { path: path }),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => {
+                    let errno = e.raw_os_error().unwrap_or(libc::EIO);
+                    return Err(Error::Sys(Errno::from_i32(errno)));
+                }
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempSocketDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -1,4 +1,8 @@
-use nix::sys::socket::{InetAddr, UnixAddr, getsockname};
+use nix::{Errno, Error};
+use nix::sys::socket::{AddressFamily, InetAddr, IpAddr, SockAddr, UnixAddr, getsockname,
+                       MAX_ABSTRACT_LEN};
+use nix::sys::socket::Ipv4Addr as NixIpv4Addr;
+use nix::sys::socket::Ipv6Addr as NixIpv6Addr;
 use std::mem;
 use std::net::{self, Ipv6Addr, SocketAddr, SocketAddrV6};
 use std::path::Path;
@@ -197,3 +201,1888 @@ pub fn test_syscontrol() {
     // requires root privileges
     // connect(fd, &sockaddr).expect("connect failed");
 }
+
+#[test]
+pub fn test_sockaddr_stable_hash() {
+    let a = SockAddr::new_inet(InetAddr::from_std(&"127.0.0.1:80".parse().unwrap()));
+    let b = SockAddr::new_inet(InetAddr::from_std(&"127.0.0.1:80".parse().unwrap()));
+    let c = SockAddr::new_inet(InetAddr::from_std(&"127.0.0.1:81".parse().unwrap()));
+    assert_eq!(a.stable_hash(), b.stable_hash());
+    assert!(a.stable_hash() != c.stable_hash());
+
+    let u1 = SockAddr::new_unix("/tmp/stable.sock").unwrap();
+    let u2 = SockAddr::new_unix("/tmp/stable.sock").unwrap();
+    assert_eq!(u1.stable_hash(), u2.stable_hash());
+}
+
+#[test]
+pub fn test_abstract_max_len() {
+    let name = vec![b'a'; MAX_ABSTRACT_LEN];
+    assert!(UnixAddr::abstract_fits(&name));
+    let addr = UnixAddr::new_abstract(&name).unwrap();
+    assert_eq!(addr.0.sun_path.len(), MAX_ABSTRACT_LEN + 1);
+
+    let name = vec![b'a'; MAX_ABSTRACT_LEN + 1];
+    assert!(!UnixAddr::abstract_fits(&name));
+    assert!(UnixAddr::new_abstract(&name).is_err());
+}
+
+#[test]
+pub fn test_inetaddr_without_scope() {
+    let ip: Ipv6Addr = "fe80::1".parse().unwrap();
+    let a = InetAddr::from_std(&SocketAddr::V6(SocketAddrV6::new(ip, 80, 0, 1)));
+    let b = InetAddr::from_std(&SocketAddr::V6(SocketAddrV6::new(ip, 80, 0, 2)));
+    assert!(a != b);
+    assert!(a.without_scope() == b.without_scope());
+
+    let v4 = InetAddr::from_std(&"10.0.0.1:80".parse().unwrap());
+    assert!(v4.without_scope() == v4);
+}
+
+#[test]
+pub fn test_ipv6_link_local_from_mac() {
+    let addr = NixIpv6Addr::link_local_from_mac([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
+    let expected: Ipv6Addr = "fe80::211:22ff:fe33:4455".parse().unwrap();
+    assert_eq!(addr.to_std(), expected);
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "openbsd",
+          target_os = "netbsd"))]
+#[test]
+pub fn test_link_addr() {
+    let mac = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55];
+    let addr = SockAddr::new_link(3, &mac).unwrap();
+    assert_eq!(addr.family(), AddressFamily::Link);
+    match addr {
+        SockAddr::Link(ref link) => {
+            assert_eq!(link.ifindex(), 3);
+            assert_eq!(link.addr(), &mac[..]);
+        }
+        _ => panic!("nope"),
+    }
+    assert_eq!(addr.to_str(), "index: 3 addr: 00:11:22:33:44:55");
+    assert!(SockAddr::new_link(3, &[0; 64]).is_err());
+}
+
+#[test]
+pub fn test_inetaddr_in_any_cidr() {
+    use nix::sys::socket::{Cidr, CidrV4, CidrV6};
+
+    let cidrs = [
+        Cidr::V4(CidrV4::new(NixIpv4Addr::new(10, 0, 0, 0), 8).unwrap()),
+        Cidr::V4(CidrV4::new(NixIpv4Addr::new(10, 1, 0, 0), 16).unwrap()),
+        Cidr::V6(CidrV6::new(NixIpv6Addr::from_std(&"2001:db8::".parse().unwrap()), 32).unwrap()),
+    ];
+
+    let in_both = InetAddr::from_std(&"10.1.2.3:80".parse().unwrap());
+    let in_one = InetAddr::from_std(&"10.2.0.1:80".parse().unwrap());
+    let outside = InetAddr::from_std(&"192.168.0.1:80".parse().unwrap());
+    let in_v6 = InetAddr::from_std(&"[2001:db8:1::1]:80".parse().unwrap());
+    let outside_v6 = InetAddr::from_std(&"[2001:db9::1]:80".parse().unwrap());
+    assert!(in_both.in_any_cidr(&cidrs));
+    assert!(in_one.in_any_cidr(&cidrs));
+    assert!(!outside.in_any_cidr(&cidrs));
+    assert!(in_v6.in_any_cidr(&cidrs));
+    assert!(!outside_v6.in_any_cidr(&cidrs));
+    assert!(!in_both.in_any_cidr(&[]));
+
+    let all = CidrV4::new(NixIpv4Addr::new(0, 0, 0, 0), 0).unwrap();
+    assert!(all.contains(&NixIpv4Addr::new(255, 255, 255, 255)));
+    assert!(CidrV4::new(NixIpv4Addr::new(0, 0, 0, 0), 33).is_err());
+    assert!(CidrV6::new(NixIpv6Addr::from_std(&"::".parse().unwrap()), 129).is_err());
+}
+
+#[test]
+pub fn test_tagged_string_roundtrip() {
+    let path = UnixAddr::new("/tmp/tagged.sock").unwrap();
+    let s = format!("{:#}", path);
+    assert_eq!(s, "unix:/tmp/tagged.sock");
+    assert!(UnixAddr::from_tagged_string(&s).unwrap() == path);
+    assert_eq!(UnixAddr::from_tagged_string(&s).unwrap().path(), path.path());
+
+    let abs = UnixAddr::new_abstract(b"tagged").unwrap();
+    let s = format!("{:#}", abs);
+    assert_eq!(s, "unix:@tagged");
+    assert!(UnixAddr::from_tagged_string(&s).unwrap() == abs);
+
+    let unnamed = UnixAddr::new("").unwrap();
+    let s = format!("{:#}", unnamed);
+    assert_eq!(s, "unix:");
+    let parsed = UnixAddr::from_tagged_string(&s).unwrap();
+    assert!(parsed == unnamed);
+    assert_eq!(parsed.1, 0);
+    assert_eq!(parsed.path(), None);
+
+    for s in &["unix:/tmp/tagged.sock", "unix:@tagged", "unix:",
+               "inet:127.0.0.1:80", "inet:[::1]:80"] {
+        let addr = SockAddr::from_tagged_string(s).unwrap();
+        assert_eq!(&format!("{:#}", addr), s);
+    }
+    assert!(SockAddr::from_tagged_string("/tmp/tagged.sock").is_err());
+    assert!(SockAddr::from_tagged_string("inet:127.0.0.1").is_err());
+}
+
+#[test]
+pub fn test_ip_addr_eq_octets() {
+    let v4 = NixIpv4Addr::new(1, 2, 3, 4);
+    assert!(v4 == [1, 2, 3, 4]);
+    assert!([1, 2, 3, 4] == v4);
+    assert!(v4 != [4, 3, 2, 1]);
+
+    let v6 = NixIpv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1);
+    let octets = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+    assert!(v6 == octets);
+    assert!(octets == v6);
+    assert!(v6 != [0; 16]);
+}
+
+#[test]
+pub fn test_sockaddr_with_port() {
+    let addr = SockAddr::new_inet(InetAddr::from_std(&"[::1]:80".parse().unwrap()));
+    let moved = addr.with_port(8080);
+    assert_eq!(moved.to_str(), "[::1]:8080");
+    match moved {
+        SockAddr::Inet(inet) => assert_eq!(inet.port(), 8080),
+        _ => panic!("nope"),
+    }
+
+    let unix = SockAddr::new_unix("/tmp/with_port.sock").unwrap();
+    assert!(unix.with_port(8080) == unix);
+}
+
+#[test]
+pub fn test_address_family_describe() {
+    assert_eq!(AddressFamily::Inet.as_str(), "AF_INET");
+    assert_eq!(AddressFamily::Inet.describe(), format!("AF_INET ({})", libc::AF_INET));
+    assert_eq!(AddressFamily::Unix.describe(), format!("AF_UNIX ({})", libc::AF_UNIX));
+}
+
+#[test]
+pub fn test_inetaddr_reject_mapped() {
+    let mapped = InetAddr::from_std(&"[::ffff:1.2.3.4]:80".parse().unwrap());
+    assert!(mapped.reject_mapped().is_err());
+
+    let v6 = InetAddr::from_std(&"[2001:db8::1]:80".parse().unwrap());
+    assert!(v6.reject_mapped().unwrap() == v6);
+
+    let v4 = InetAddr::from_std(&"1.2.3.4:80".parse().unwrap());
+    assert!(v4.reject_mapped().is_ok());
+}
+
+#[test]
+pub fn test_ip_addr_binary() {
+    let mask = NixIpv4Addr::new(255, 0, 0, 0);
+    assert_eq!(format!("{:b}", mask), "11111111000000000000000000000000");
+    assert_eq!(format!("{:#b}", mask), "11111111.00000000.00000000.00000000");
+
+    let v6 = NixIpv6Addr::new(0x8000, 0, 0, 0, 0, 0, 0, 1);
+    let bits = format!("{:b}", v6);
+    assert_eq!(bits.len(), 128);
+    assert!(bits.starts_with("1000000000000000"));
+    assert!(bits.ends_with("0000000000000001"));
+    assert_eq!(format!("{:#b}", v6).split(':').count(), 8);
+}
+
+#[test]
+pub fn test_sockaddr_from_msghdr_name() {
+    use std::ptr;
+
+    let inet = SockAddr::new_inet(InetAddr::from_std(&"127.0.0.1:80".parse().unwrap()));
+    let unix = SockAddr::new_unix("/tmp/msghdr.sock").unwrap();
+    for addr in &[inet, unix] {
+        let converted = unsafe {
+            let (sa, len) = addr.as_ffi_pair();
+            SockAddr::from_msghdr_name(sa, len)
+        };
+        assert!(converted.unwrap() == *addr);
+    }
+
+    unsafe {
+        assert!(SockAddr::from_msghdr_name(ptr::null(), 0).is_none());
+        let (sa, _) = inet.as_ffi_pair();
+        assert!(SockAddr::from_msghdr_name(sa, 0).is_none());
+        assert!(SockAddr::from_msghdr_name(sa, 4).is_none());
+    }
+}
+
+#[test]
+pub fn test_unixaddr_from_systemd_notify() {
+    let path = UnixAddr::from_systemd_notify(b"/run/systemd/notify").unwrap();
+    assert_eq!(path.path(), Some(Path::new("/run/systemd/notify")));
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let abs = UnixAddr::from_systemd_notify(b"@/org/freedesktop/systemd1/notify").unwrap();
+        assert_eq!(abs.path(), None);
+        assert!(abs == UnixAddr::new_abstract(b"/org/freedesktop/systemd1/notify").unwrap());
+    }
+
+    assert!(UnixAddr::from_systemd_notify(b"").is_err());
+}
+
+#[test]
+pub fn test_inetaddr_any_like() {
+    let v4 = InetAddr::from_std(&"192.168.1.1:80".parse().unwrap());
+    assert_eq!(v4.any_like(8080).to_str(), "0.0.0.0:8080");
+
+    let v6 = InetAddr::from_std(&"[2001:db8::1]:80".parse().unwrap());
+    assert_eq!(v6.any_like(8080).to_str(), "[::]:8080");
+}
+
+#[test]
+pub fn test_ip_addr_from_str() {
+    let v4: NixIpv4Addr = "1.2.3.4".parse().unwrap();
+    assert!(v4 == [1, 2, 3, 4]);
+    assert!("1.2.3".parse::<NixIpv4Addr>().is_err());
+
+    let loopback: Ipv6Addr = "::1".parse().unwrap();
+    let bare: NixIpv6Addr = "::1".parse().unwrap();
+    let bracketed: NixIpv6Addr = "[::1]".parse().unwrap();
+    assert_eq!(bare.to_std(), loopback);
+    assert_eq!(bracketed.to_std(), loopback);
+    assert!("[::1".parse::<NixIpv6Addr>().is_err());
+    assert!("::1]".parse::<NixIpv6Addr>().is_err());
+    assert!("[[::1]]".parse::<NixIpv6Addr>().is_err());
+}
+
+#[test]
+pub fn test_sockaddr_metric_key() {
+    let v4 = SockAddr::new_inet(InetAddr::from_std(&"127.0.0.1:80".parse().unwrap()));
+    assert_eq!(v4.metric_key(), "inet4:7f000001:0050");
+
+    let v6 = SockAddr::new_inet(InetAddr::from_std(&"[::1]:80".parse().unwrap()));
+    assert_eq!(v6.metric_key(), "inet6:00000000000000000000000000000001:0050");
+    let flow = SocketAddrV6::new("::1".parse().unwrap(), 80, 0x12345, 2);
+    assert_eq!(SockAddr::new_inet(InetAddr::from_std(&SocketAddr::V6(flow))).metric_key(),
+               "inet6:00000000000000000000000000000001:0050/00012345%2");
+
+    let addrs = [
+        v4,
+        v4.with_port(81),
+        v6,
+        SockAddr::new_inet(InetAddr::from_std(&"[fe80::1%2]:80".parse().unwrap())),
+        SockAddr::new_inet(InetAddr::from_std(&"[fe80::1%3]:80".parse().unwrap())),
+        SockAddr::new_inet(InetAddr::from_std(&SocketAddr::V6(
+            SocketAddrV6::new("fe80::1".parse().unwrap(), 80, 0x12345, 3)))),
+        SockAddr::new_unix("/tmp/key.sock").unwrap(),
+        SockAddr::Unix(UnixAddr::new_abstract(b"/tmp/key.sock").unwrap()),
+    ];
+    for (i, a) in addrs.iter().enumerate() {
+        for (j, b) in addrs.iter().enumerate() {
+            assert_eq!(i == j, a.metric_key() == b.metric_key());
+        }
+    }
+}
+
+#[test]
+pub fn test_ip_addr_common_prefix_len() {
+    let a: NixIpv6Addr = "2001:db8::1".parse().unwrap();
+    let b: NixIpv6Addr = "2001:db8::2".parse().unwrap();
+    let c: NixIpv6Addr = "a001:db8::1".parse().unwrap();
+    assert_eq!(a.common_prefix_len(&a), 128);
+    assert_eq!(a.common_prefix_len(&b), 126);
+    assert_eq!(a.common_prefix_len(&c), 0);
+
+    let x = NixIpv4Addr::new(192, 168, 1, 1);
+    assert_eq!(x.common_prefix_len(&x), 32);
+    assert_eq!(x.common_prefix_len(&NixIpv4Addr::new(192, 168, 2, 1)), 22);
+    assert_eq!(x.common_prefix_len(&NixIpv4Addr::new(64, 168, 1, 1)), 0);
+}
+
+#[test]
+pub fn test_unixaddr_sun_family() {
+    let addr = UnixAddr::new("/tmp/family.sock").unwrap();
+    assert_eq!(addr.sun_family() as i32, libc::AF_UNIX);
+}
+
+#[test]
+pub fn test_inetaddr_to_mapped_bytes() {
+    let v4 = InetAddr::from_std(&"1.2.3.4:80".parse().unwrap());
+    let mapped: Ipv6Addr = "::ffff:1.2.3.4".parse().unwrap();
+    assert_eq!(v4.to_mapped_bytes(), mapped.octets());
+
+    let v6 = InetAddr::from_std(&"[2001:db8::1]:80".parse().unwrap());
+    let ip: Ipv6Addr = "2001:db8::1".parse().unwrap();
+    assert_eq!(v6.to_mapped_bytes(), ip.octets());
+}
+
+// The converted address must not alias the buffer it was converted from
+#[test]
+pub fn test_sockaddr_conversion_copies() {
+    use nix::sys::socket::{sockaddr_storage, sockaddr_storage_to_addr};
+    use std::ptr;
+
+    let inet = SockAddr::new_inet(InetAddr::from_std(&"[fe80::1%2]:80".parse().unwrap()));
+    let unix = SockAddr::new_unix("/tmp/copies.sock").unwrap();
+    for addr in &[inet, unix] {
+        let mut storage: Box<sockaddr_storage> = Box::new(unsafe { mem::zeroed() });
+        let (converted, converted2) = unsafe {
+            let (sa, len) = addr.as_ffi_pair();
+            ptr::copy_nonoverlapping(sa as *const _ as *const u8,
+                                     &mut *storage as *mut _ as *mut u8,
+                                     len as usize);
+            let converted = sockaddr_storage_to_addr(&storage, len as usize).unwrap();
+            let converted2 = SockAddr::from_msghdr_name(
+                &*storage as *const _ as *const libc::sockaddr, len).unwrap();
+            (converted, converted2)
+        };
+
+        // Scribble over the source buffer, then free it
+        unsafe {
+            ptr::write_bytes(&mut *storage as *mut sockaddr_storage as *mut u8,
+                             0xa5, mem::size_of::<sockaddr_storage>());
+        }
+        drop(storage);
+
+        assert!(converted == *addr);
+        assert!(converted2 == *addr);
+        assert_eq!(converted.to_str(), addr.to_str());
+    }
+}
+
+#[cfg(feature = "rand")]
+#[test]
+pub fn test_ip_addr_from_rng() {
+    use rand::{SeedableRng, XorShiftRng};
+
+    let seed = [1, 2, 3, 4];
+    let mut rng1 = XorShiftRng::from_seed(seed);
+    let mut rng2 = XorShiftRng::from_seed(seed);
+    for _ in 0..16 {
+        assert!(NixIpv4Addr::from_rng(&mut rng1) == NixIpv4Addr::from_rng(&mut rng2));
+        assert_eq!(NixIpv6Addr::from_rng(&mut rng1).to_std(),
+                   NixIpv6Addr::from_rng(&mut rng2).to_std());
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+pub fn test_xdp_addr() {
+    use nix::sys::socket::{sockaddr_storage, sockaddr_storage_to_addr};
+    use std::ptr;
+
+    let addr = SockAddr::new_xdp(2, 5, libc::XDP_ZEROCOPY, 0);
+    assert_eq!(addr.family(), AddressFamily::Xdp);
+    match addr {
+        SockAddr::Xdp(ref xdp) => {
+            assert_eq!(xdp.ifindex(), 2);
+            assert_eq!(xdp.queue_id(), 5);
+            assert_eq!(xdp.flags(), libc::XDP_ZEROCOPY);
+        }
+        _ => panic!("nope"),
+    }
+
+    let converted = unsafe {
+        let (sa, len) = addr.as_ffi_pair();
+        assert_eq!(len as usize, mem::size_of::<libc::sockaddr_xdp>());
+        assert_eq!(sa.sa_family as i32, libc::AF_XDP);
+        let mut storage: sockaddr_storage = mem::zeroed();
+        ptr::copy_nonoverlapping(sa as *const _ as *const u8,
+                                 &mut storage as *mut _ as *mut u8,
+                                 len as usize);
+        sockaddr_storage_to_addr(&storage, len as usize).unwrap()
+    };
+    assert!(converted == addr);
+}
+
+// Parsing and formatting are implemented in Rust rather than with
+// inet_pton/inet_ntop, and must agree with std
+#[test]
+pub fn test_ip_addr_text_matches_std() {
+    use std::net::Ipv4Addr;
+
+    for s in &["0.0.0.0", "127.0.0.1", "10.20.30.40", "255.255.255.255"] {
+        let std: Ipv4Addr = s.parse().unwrap();
+        let nix: NixIpv4Addr = s.parse().unwrap();
+        assert_eq!(nix.to_std(), std);
+        assert_eq!(nix.to_string(), std.to_string());
+    }
+    for s in &["::", "::1", "fe80::1", "2001:db8::ff00:42:8329",
+               "2001:DB8:0:0:8:800:200C:417A", "::ffff:1.2.3.4", "1:2:3:4:5:6:7:8"] {
+        let std: Ipv6Addr = s.parse().unwrap();
+        let nix: NixIpv6Addr = s.parse().unwrap();
+        assert_eq!(nix.to_std(), std);
+        assert_eq!(nix.to_string(), std.to_string());
+    }
+    for s in &["", "1.2.3.4.5", "256.0.0.1", "1.2.3.-4", ":::", "1:2:3:4:5:6:7:8:9", "g::1"] {
+        assert_eq!(s.parse::<NixIpv4Addr>().is_ok(), s.parse::<Ipv4Addr>().is_ok());
+        assert_eq!(s.parse::<NixIpv6Addr>().is_ok(), s.parse::<Ipv6Addr>().is_ok());
+    }
+}
+
+#[test]
+pub fn test_unixaddr_matches_glob() {
+    let addr = UnixAddr::new("/run/app-1.sock").unwrap();
+    assert!(addr.matches_glob("/run/app-*.sock"));
+    assert!(addr.matches_glob("/run/app-?.sock"));
+    assert!(addr.matches_glob("/run/*"));
+    assert!(addr.matches_glob("/run/app-1.sock"));
+    assert!(!addr.matches_glob("/run/app-??.sock"));
+    assert!(!addr.matches_glob("/run/other-*.sock"));
+    assert!(!addr.matches_glob("/*.sock"));
+    assert!(!addr.matches_glob("/run/app-1.sock2"));
+
+    let abs = UnixAddr::new_abstract(b"/run/app-1.sock").unwrap();
+    assert!(!abs.matches_glob("*"));
+}
+
+#[test]
+pub fn test_inetaddr_hash_ignoring_scope() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    fn hash(addr: &InetAddr) -> u64 {
+        let mut h = DefaultHasher::new();
+        addr.hash_ignoring_scope(&mut h);
+        h.finish()
+    }
+
+    let ip: Ipv6Addr = "fe80::1".parse().unwrap();
+    let a = InetAddr::from_std(&SocketAddr::V6(SocketAddrV6::new(ip, 80, 7, 1)));
+    let b = InetAddr::from_std(&SocketAddr::V6(SocketAddrV6::new(ip, 80, 7, 2)));
+    let c = InetAddr::from_std(&SocketAddr::V6(SocketAddrV6::new(ip, 81, 7, 1)));
+    assert!(a.without_scope() == b.without_scope());
+    assert_eq!(hash(&a), hash(&b));
+    assert!(hash(&a) != hash(&c));
+}
+
+#[test]
+pub fn test_ipv6_is_documentation() {
+    assert!("2001:db8::1".parse::<NixIpv6Addr>().unwrap().is_documentation());
+    assert!("2001:db8:ffff::".parse::<NixIpv6Addr>().unwrap().is_documentation());
+    assert!(!"2001:db9::1".parse::<NixIpv6Addr>().unwrap().is_documentation());
+    assert!(!"2606:4700::1111".parse::<NixIpv6Addr>().unwrap().is_documentation());
+}
+
+#[test]
+pub fn test_inetaddr_to_authority() {
+    let v4 = InetAddr::from_std(&"127.0.0.1:80".parse().unwrap());
+    assert_eq!(v4.to_authority(), "127.0.0.1:80");
+
+    let v6 = InetAddr::from_std(&"[::1]:80".parse().unwrap());
+    assert_eq!(v6.to_authority(), "[::1]:80");
+
+    let mapped = InetAddr::from_std(&"[::ffff:1.2.3.4]:443".parse().unwrap());
+    assert_eq!(mapped.to_authority(), "[::ffff:1.2.3.4]:443");
+}
+
+#[test]
+pub fn test_unixaddr_new_strict() {
+    assert!(UnixAddr::new_strict("/tmp/s").is_ok());
+    assert_eq!(UnixAddr::new_strict("/tmp/s/").err(), Some(Error::Sys(Errno::EISDIR)));
+    assert!(UnixAddr::new("/tmp/s/").is_ok());
+}
+
+#[test]
+pub fn test_ip_addr_be_bytes_vec() {
+    let v4 = NixIpv4Addr::new(192, 0, 2, 1);
+    let bytes = v4.to_be_bytes_vec();
+    assert_eq!(bytes, vec![192, 0, 2, 1]);
+    assert!(NixIpv4Addr::from_be_bytes_slice(&bytes).unwrap() == v4);
+    assert!(NixIpv4Addr::from_be_bytes_slice(&[1, 2, 3]).is_err());
+
+    let v6: NixIpv6Addr = "2001:db8::1".parse().unwrap();
+    let bytes = v6.to_be_bytes_vec();
+    assert_eq!(bytes.len(), 16);
+    assert_eq!(&bytes[..4], &[0x20, 0x01, 0x0d, 0xb8]);
+    assert!(NixIpv6Addr::from_be_bytes_slice(&bytes).unwrap() == v6.octets());
+    assert!(NixIpv6Addr::from_be_bytes_slice(&bytes[..15]).is_err());
+}
+
+#[test]
+pub fn test_sockaddr_identity_key() {
+    let a = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 80));
+    let b = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 80));
+    let c = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 81));
+    let u = SockAddr::new_unix("/tmp/s").unwrap();
+
+    assert_eq!(a.identity_key(), b.identity_key());
+    assert!(a.identity_key() != c.identity_key());
+    assert!(a.identity_key() != u.identity_key());
+    assert_eq!(u.identity_key(), SockAddr::new_unix("/tmp/s").unwrap().identity_key());
+    assert_eq!(a.stable_hash(), b.stable_hash());
+
+    let mut other_family = UnixAddr::new("/tmp/s").unwrap();
+    other_family.0.sun_family = 0;
+    assert!(SockAddr::Unix(other_family) != u);
+    assert!(SockAddr::Unix(other_family).identity_key() != u.identity_key());
+}
+
+#[test]
+pub fn test_inetaddr_is_suspicious() {
+    use std::net::{SocketAddr, SocketAddrV6};
+
+    let scoped = |ip: &str, scope| {
+        InetAddr::from_std(&SocketAddr::V6(SocketAddrV6::new(ip.parse().unwrap(), 0, 0, scope)))
+    };
+    assert!(scoped("::", 2).is_suspicious());
+    assert!(scoped("::1", 2).is_suspicious());
+    assert!(!scoped("fe80::1", 2).is_suspicious());
+    assert!(!scoped("::", 0).is_suspicious());
+    assert!(!InetAddr::from_std(&"127.0.0.1:80".parse().unwrap()).is_suspicious());
+}
+
+#[test]
+pub fn test_ipv6addr_segments() {
+    let addr = NixIpv6Addr::new(0x2001, 0xdb8, 0, 0x1234, 0xff00, 0x00ff, 0x8000, 1);
+    assert_eq!(addr.segments(), [0x2001, 0xdb8, 0, 0x1234, 0xff00, 0x00ff, 0x8000, 1]);
+
+    let octets = addr.octets();
+    let expected: Vec<u16> = (0..8)
+        .map(|i| ((octets[2 * i] as u16) << 8) + octets[2 * i + 1] as u16)
+        .collect();
+    assert_eq!(&addr.segments()[..], &expected[..]);
+    assert_eq!(addr.segments(), addr.to_std().segments());
+}
+
+#[test]
+pub fn test_addressfamily_raw_protocol_for() {
+    use nix::sys::socket::Transport;
+
+    assert_eq!(AddressFamily::Inet.raw_protocol_for(Transport::Tcp), Some(libc::IPPROTO_TCP));
+    assert_eq!(AddressFamily::Inet6.raw_protocol_for(Transport::Udp), Some(libc::IPPROTO_UDP));
+    assert_eq!(AddressFamily::Inet.raw_protocol_for(Transport::Icmp), Some(libc::IPPROTO_ICMP));
+    assert_eq!(AddressFamily::Inet6.raw_protocol_for(Transport::Icmp), Some(libc::IPPROTO_ICMPV6));
+    assert_eq!(AddressFamily::Unix.raw_protocol_for(Transport::Tcp), None);
+}
+
+#[test]
+pub fn test_sockaddr_same_host() {
+    let a = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(10, 0, 0, 1), 80));
+    let b = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(10, 0, 0, 1), 443));
+    let c = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(10, 0, 0, 2), 80));
+    let u = SockAddr::new_unix("/tmp/s").unwrap();
+
+    assert!(a.same_host(&b));
+    assert!(!a.same_host(&c));
+    assert!(!a.same_host(&u));
+    assert!(u.same_host(&SockAddr::new_unix("/tmp/s").unwrap()));
+}
+
+#[test]
+pub fn test_ip_addr_to_host_cidr() {
+    let v4 = NixIpv4Addr::new(192, 0, 2, 1);
+    let cidr = v4.to_host_cidr();
+    assert_eq!(cidr.prefix_len(), 32);
+    assert!(cidr.addr() == v4);
+    assert!(cidr.contains(&v4));
+    assert!(!cidr.contains(&NixIpv4Addr::new(192, 0, 2, 2)));
+
+    let v6: NixIpv6Addr = "2001:db8::1".parse().unwrap();
+    let cidr = v6.to_host_cidr();
+    assert_eq!(cidr.prefix_len(), 128);
+    assert!(cidr.addr() == v6.octets());
+    assert!(cidr.contains(&v6));
+}
+
+#[test]
+pub fn test_unixaddr_ffi_len() {
+    let offset = {
+        let sa: libc::sockaddr_un = unsafe { mem::zeroed() };
+        (&sa.sun_path as *const _ as usize) - (&sa as *const _ as usize)
+    };
+
+    let path = UnixAddr::new("/tmp/s").unwrap();
+    assert_eq!(path.ffi_len() as usize, offset + 6);
+    assert_eq!(unsafe { SockAddr::Unix(path).as_ffi_pair() }.1, path.ffi_len());
+
+    let unnamed = UnixAddr::from_tagged_string("unix:").unwrap();
+    assert_eq!(unnamed.ffi_len() as usize, offset);
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let abs = UnixAddr::new_abstract(b"name").unwrap();
+        assert_eq!(abs.ffi_len() as usize, offset + 5);
+    }
+}
+
+#[test]
+pub fn test_inetaddr_as_ffi_pair() {
+    let v4 = InetAddr::from_std(&"127.0.0.1:80".parse().unwrap());
+    let (sa, len) = unsafe { v4.as_ffi_pair() };
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_in>());
+    assert_eq!(sa.sa_family as i32, libc::AF_INET);
+
+    let v6 = InetAddr::from_std(&"[::1]:80".parse().unwrap());
+    let (sa, len) = unsafe { v6.as_ffi_pair() };
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_in6>());
+    assert_eq!(sa.sa_family as i32, libc::AF_INET6);
+}
+
+#[test]
+pub fn test_sockaddr_from_accept_unix() {
+    let offset = {
+        let sa: libc::sockaddr_un = unsafe { mem::zeroed() };
+        (&sa.sun_path as *const _ as usize) - (&sa as *const _ as usize)
+    };
+
+    // An unbound peer: the kernel reports just the family
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    storage.ss_family = AddressFamily::Unix as libc::sa_family_t;
+    match SockAddr::from_accept(&storage, offset as libc::socklen_t) {
+        Some(SockAddr::Unix(ref unix)) => {
+            assert_eq!(unix.path(), None);
+            assert_eq!(format!("{:#}", unix), "unix:");
+        }
+        _ => panic!("expected an unnamed unix address"),
+    }
+
+    // A pathname peer, with the terminating null counted in the length
+    let expected = UnixAddr::new("/tmp/s").unwrap();
+    unsafe {
+        let sun = &mut *(&mut storage as *mut _ as *mut libc::sockaddr_un);
+        for (dst, &src) in sun.sun_path.iter_mut().zip(b"/tmp/s\0") {
+            *dst = src as libc::c_char;
+        }
+    }
+    let len = (offset + 7) as libc::socklen_t;
+    assert!(SockAddr::from_accept(&storage, len) == Some(SockAddr::Unix(expected)));
+}
+
+#[test]
+pub fn test_ip_addr_parse_rejects_non_ascii() {
+    // U+FF11 FULLWIDTH DIGIT ONE
+    assert!("\u{ff11}92.0.2.1".parse::<NixIpv4Addr>().is_err());
+    assert!("192.0.2.\u{0661}".parse::<NixIpv4Addr>().is_err());
+    assert!("2001:db8::\u{ff11}".parse::<NixIpv6Addr>().is_err());
+    assert!("[2001:db8::\u{ff41}]".parse::<NixIpv6Addr>().is_err());
+
+    assert!("192.0.2.1".parse::<NixIpv4Addr>().is_ok());
+    assert!("2001:db8::1".parse::<NixIpv6Addr>().is_ok());
+}
+
+#[test]
+pub fn test_sockaddr_family_name() {
+    let v4 = SockAddr::new_inet(InetAddr::from_std(&"127.0.0.1:80".parse().unwrap()));
+    let v6 = SockAddr::new_inet(InetAddr::from_std(&"[::1]:80".parse().unwrap()));
+    let unix = SockAddr::new_unix("/tmp/s").unwrap();
+
+    assert_eq!(v4.family_name(), "AF_INET");
+    assert_eq!(v6.family_name(), "AF_INET6");
+    assert_eq!(unix.family_name(), "AF_UNIX");
+    for addr in &[v4, v6, unix] {
+        assert_eq!(addr.family_name(), addr.family().as_str());
+    }
+}
+
+#[test]
+pub fn test_unixaddr_with_path() {
+    use std::iter;
+
+    let path = UnixAddr::new("/tmp/a").unwrap();
+    let moved = path.with_path(Path::new("/tmp/b")).unwrap();
+    assert_eq!(moved.path(), Some(Path::new("/tmp/b")));
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let abs = UnixAddr::new_abstract(b"name").unwrap();
+        let moved = abs.with_path(Path::new("/tmp/b")).unwrap();
+        assert_eq!(moved.path(), Some(Path::new("/tmp/b")));
+        assert!(moved == UnixAddr::new("/tmp/b").unwrap());
+    }
+
+    let long: String = iter::repeat('/').take(200).collect();
+    assert_eq!(path.with_path(Path::new(&long)).err(), Some(Error::Sys(Errno::ENAMETOOLONG)));
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+pub fn test_addr_invariants() {
+    use nix::sys::socket::test_util::assert_addr_invariants;
+
+    let inet = ["0.0.0.0:0", "127.0.0.1:80", "255.255.255.255:65535",
+                "[::]:0", "[::1]:443", "[fe80::1%3]:22", "[::ffff:1.2.3.4]:8080"];
+    for s in &inet {
+        assert_addr_invariants(&InetAddr::from_std(&s.parse().unwrap()));
+    }
+
+    let v4 = [NixIpv4Addr::new(0, 0, 0, 0), NixIpv4Addr::new(10, 1, 2, 3),
+              NixIpv4Addr::new(224, 0, 0, 1), NixIpv4Addr::new(255, 255, 255, 255)];
+    for ip in &v4 {
+        assert_addr_invariants(ip);
+    }
+
+    for s in &["::", "::1", "ff02::1", "2001:db8::1"] {
+        assert_addr_invariants(&s.parse::<nix::sys::socket::Ipv6Addr>().unwrap());
+    }
+}
+
+#[test]
+pub fn test_ipv6addr_network() {
+    let addr: NixIpv6Addr = "2001:db8:1234::1".parse().unwrap();
+    assert_eq!(addr.network(32).to_std(), "2001:db8::".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(addr.network(36).to_std(), "2001:db8:1000::".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(addr.network(0).to_std(), "::".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(addr.network(128).to_std(), addr.to_std());
+    assert_eq!(addr.network(200).to_std(), addr.to_std());
+}
+
+#[test]
+pub fn test_sockaddr_conflicts_with() {
+    let inet = |s: &str| SockAddr::new_inet(InetAddr::from_std(&s.parse().unwrap()));
+
+    // exact match
+    assert!(inet("127.0.0.1:80").conflicts_with(&inet("127.0.0.1:80")));
+    assert!(inet("[::1]:80").conflicts_with(&inet("[::1]:80")));
+    // wildcard vs specific
+    assert!(inet("0.0.0.0:80").conflicts_with(&inet("127.0.0.1:80")));
+    assert!(inet("[::1]:80").conflicts_with(&inet("[::]:80")));
+    assert!(inet("[::]:80").conflicts_with(&inet("10.0.0.1:80")));
+    assert!(inet("[::ffff:10.0.0.1]:80").conflicts_with(&inet("0.0.0.0:80")));
+    assert!(!inet("0.0.0.0:80").conflicts_with(&inet("[::1]:80")));
+    // different ports or addresses
+    assert!(!inet("127.0.0.1:80").conflicts_with(&inet("127.0.0.1:81")));
+    assert!(!inet("127.0.0.1:80").conflicts_with(&inet("127.0.0.2:80")));
+    assert!(!inet("0.0.0.0:0").conflicts_with(&inet("0.0.0.0:0")));
+
+    let unix = SockAddr::new_unix("/tmp/s").unwrap();
+    assert!(unix.conflicts_with(&SockAddr::new_unix("/tmp/s").unwrap()));
+    assert!(!unix.conflicts_with(&inet("127.0.0.1:80")));
+}
+
+#[test]
+pub fn test_unixaddr_parent_dir() {
+    let addr = UnixAddr::new("/run/app/x.sock").unwrap();
+    assert_eq!(addr.parent_dir(), Some(Path::new("/run/app")));
+    assert_eq!(UnixAddr::new("/").unwrap().parent_dir(), None);
+    assert_eq!(UnixAddr::from_tagged_string("unix:").unwrap().parent_dir(), None);
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    assert_eq!(UnixAddr::new_abstract(b"x").unwrap().parent_dir(), None);
+}
+
+#[test]
+pub fn test_inetaddr_tagged_encoding() {
+    let v4 = InetAddr::from_std(&"192.0.2.1:443".parse().unwrap());
+    let bytes = v4.encode_tagged();
+    assert_eq!(bytes, vec![4, 192, 0, 2, 1, 0x01, 0xbb]);
+    assert!(InetAddr::decode_tagged(&bytes).unwrap() == v4);
+
+    let v6 = InetAddr::from_std(&"[2001:db8::1]:80".parse().unwrap());
+    let bytes = v6.encode_tagged();
+    assert_eq!(bytes.len(), 19);
+    assert_eq!(bytes[0], 6);
+    assert!(InetAddr::decode_tagged(&bytes).unwrap() == v6);
+
+    assert!(InetAddr::decode_tagged(&bytes[..18]).is_err());
+    assert!(InetAddr::decode_tagged(&[4, 1, 2, 3, 4, 0]).is_err());
+    assert!(InetAddr::decode_tagged(&[5, 1, 2, 3, 4, 0, 80]).is_err());
+    assert!(InetAddr::decode_tagged(&[]).is_err());
+}
+
+#[test]
+pub fn test_ip_addr_eq_bits() {
+    let v4 = NixIpv4Addr::new(127, 0, 0, 1);
+    assert!(v4.eq_bits(0x7f000001));
+    assert!(!v4.eq_bits(0x0100007f));
+
+    let v6: NixIpv6Addr = "::1".parse().unwrap();
+    assert!(v6.eq_bits((0, 1)));
+    let v6: NixIpv6Addr = "2001:db8::1".parse().unwrap();
+    assert!(v6.eq_bits((0x2001_0db8_0000_0000, 1)));
+    assert!(!v6.eq_bits((0, 1)));
+    assert!(!v6.eq_bits((1, 0x2001_0db8_0000_0000)));
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_keyaddr() {
+    use nix::sys::socket::KeyAddr;
+
+    let addr = SockAddr::new_key();
+    assert_eq!(addr.family(), AddressFamily::Key);
+    assert_eq!(addr.family_name(), "AF_KEY");
+    assert!(addr == SockAddr::Key(KeyAddr::new()));
+
+    let (sa, len) = unsafe { addr.as_ffi_pair() };
+    assert_eq!(sa.sa_family as i32, libc::AF_KEY);
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr>());
+    unsafe {
+        assert!(SockAddr::from_msghdr_name(sa, len).unwrap() == addr);
+        assert!(SockAddr::from_msghdr_name(sa, len - 1).is_none());
+    }
+}
+
+#[test]
+pub fn test_unixaddr_abstract_has_prefix() {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let addr = UnixAddr::new_abstract(b"myapp/control").unwrap();
+        assert!(addr.abstract_has_prefix(b"myapp/"));
+        assert!(addr.abstract_has_prefix(b""));
+        assert!(!addr.abstract_has_prefix(b"other/"));
+        assert!(!addr.abstract_has_prefix(b"myapp/control/more"));
+    }
+
+    assert!(!UnixAddr::new("myapp/control").unwrap().abstract_has_prefix(b"myapp/"));
+    assert!(!UnixAddr::from_tagged_string("unix:").unwrap().abstract_has_prefix(b""));
+}
+
+#[test]
+pub fn test_inetaddr_to_dualstack() {
+    let v4 = InetAddr::from_std(&"192.0.2.1:8080".parse().unwrap());
+    let mapped = v4.to_dualstack();
+    assert_eq!(mapped.port(), 8080);
+    assert_eq!(mapped.to_std(), "[::ffff:192.0.2.1]:8080".parse().unwrap());
+    assert!(mapped.reject_mapped().is_err());
+
+    let v6 = InetAddr::from_std(&"[2001:db8::1]:80".parse().unwrap());
+    assert!(v6.to_dualstack() == v6);
+}
+
+#[test]
+pub fn test_ipv6addr_special_purpose() {
+    use nix::sys::socket::SpecialPurpose;
+
+    let cases = [
+        ("::", Some(SpecialPurpose::Unspecified)),
+        ("::1", Some(SpecialPurpose::Loopback)),
+        ("::ffff:192.0.2.1", Some(SpecialPurpose::Ipv4Mapped)),
+        ("64:ff9b::192.0.2.1", Some(SpecialPurpose::Ipv4Translation)),
+        ("64:ff9b:1::1", Some(SpecialPurpose::Ipv4Translation)),
+        ("100::1", Some(SpecialPurpose::DiscardOnly)),
+        ("2001::1", Some(SpecialPurpose::Teredo)),
+        ("2001:2::1", Some(SpecialPurpose::Benchmarking)),
+        ("2001:10::1", Some(SpecialPurpose::Orchid)),
+        ("2001:2f::1", Some(SpecialPurpose::Orchid)),
+        ("2001:db8::1", Some(SpecialPurpose::Documentation)),
+        ("2002:c000:201::1", Some(SpecialPurpose::SixToFour)),
+        ("fd12:3456::1", Some(SpecialPurpose::UniqueLocal)),
+        ("fe80::1", Some(SpecialPurpose::LinkLocal)),
+        ("febf::1", Some(SpecialPurpose::LinkLocal)),
+        ("ff02::1", Some(SpecialPurpose::Multicast)),
+        ("2606:4700::1111", None),
+        ("2001:30::1", None),
+        ("64:ff9b:2::1", None),
+    ];
+    for &(s, expected) in &cases {
+        let addr: NixIpv6Addr = s.parse().unwrap();
+        assert_eq!(addr.special_purpose(), expected, "{}", s);
+    }
+}
+
+#[test]
+pub fn test_sockaddr_anonymized() {
+    let inet = |s: &str| SockAddr::new_inet(InetAddr::from_std(&s.parse().unwrap()));
+
+    assert!(inet("192.0.2.123:443").anonymized() == inet("192.0.2.0:443"));
+    assert!(inet("[2001:db8:1:2:3:4:5:6]:80").anonymized() == inet("[2001:db8:1::]:80"));
+
+    let unix = SockAddr::new_unix("/home/alice/.app/control.sock").unwrap();
+    assert!(unix.anonymized() == SockAddr::new_unix("control.sock").unwrap());
+
+    // Raw addresses may carry a null byte inside the path; nothing after it
+    // is part of the path
+    let mut bytes = SockAddr::new_unix("/tmp/a/bxc").unwrap().to_bytes();
+    let x = bytes.iter().position(|&b| b == b'x').unwrap();
+    bytes[x] = 0;
+    let raw = SockAddr::from_bytes(&bytes).unwrap();
+    assert!(raw.anonymized() == SockAddr::new_unix("b").unwrap());
+}
+
+#[test]
+pub fn test_ip_addr_netmask() {
+    assert!(NixIpv4Addr::netmask(24) == NixIpv4Addr::new(255, 255, 255, 0));
+    assert!(NixIpv4Addr::netmask(0) == NixIpv4Addr::new(0, 0, 0, 0));
+    assert!(NixIpv4Addr::netmask(20) == NixIpv4Addr::new(255, 255, 240, 0));
+    assert!(NixIpv4Addr::netmask(40) == NixIpv4Addr::new(255, 255, 255, 255));
+
+    assert_eq!(NixIpv6Addr::netmask(32).to_std(), "ffff:ffff::".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(NixIpv6Addr::netmask(0).to_std(), "::".parse::<Ipv6Addr>().unwrap());
+    assert!(NixIpv6Addr::netmask(255).eq_bits((!0, !0)));
+}
+
+#[test]
+pub fn test_inetaddr_is_wildcard() {
+    let inet = |s: &str| InetAddr::from_std(&s.parse().unwrap());
+    assert!(inet("0.0.0.0:80").is_wildcard());
+    assert!(inet("0.0.0.0:0").is_wildcard());
+    assert!(inet("[::]:80").is_wildcard());
+    assert!(!inet("127.0.0.1:80").is_wildcard());
+    assert!(!inet("[::1]:80").is_wildcard());
+}
+
+#[test]
+pub fn test_sockaddr_with_resolved_host() {
+    let template = SockAddr::new_inet(InetAddr::from_std(&"192.0.2.1:8443".parse().unwrap()));
+    let addrs = SockAddr::with_resolved_host("localhost", &template).unwrap();
+    assert!(!addrs.is_empty());
+    for addr in &addrs {
+        match *addr {
+            SockAddr::Inet(ref inet) => assert_eq!(inet.port(), 8443),
+            _ => panic!("resolved to a non-inet address"),
+        }
+    }
+
+    let unix = SockAddr::new_unix("/tmp/s").unwrap();
+    assert!(SockAddr::with_resolved_host("localhost", &unix).is_err());
+}
+
+#[test]
+pub fn test_unixaddr_display_truncated() {
+    let long = UnixAddr::new("/run/some/deeply/nested/app/x.sock").unwrap();
+    assert_eq!(long.display_truncated(15), "/run/s...x.sock");
+    assert_eq!(long.display_truncated(15).len(), 15);
+    assert_eq!(long.display_truncated(2), "..");
+
+    let short = UnixAddr::new("/run/x.sock").unwrap();
+    assert_eq!(short.display_truncated(15), "/run/x.sock");
+
+    let unnamed = UnixAddr::from_tagged_string("unix:").unwrap();
+    assert_eq!(unnamed.display_truncated(5), unnamed.to_string());
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let abs = UnixAddr::new_abstract(b"a-rather-long-abstract-name").unwrap();
+        assert_eq!(abs.display_truncated(5), "@a-rather-long-abstract-name");
+    }
+}
+
+#[test]
+pub fn test_ipv6addr_interface_id() {
+    let addr: NixIpv6Addr = "fe80::211:22ff:fe33:4455".parse().unwrap();
+    assert_eq!(addr.interface_id(), 0x0211_22ff_fe33_4455);
+
+    let other = addr.with_interface_id(0x1234_5678_9abc_def0);
+    assert_eq!(other.interface_id(), 0x1234_5678_9abc_def0);
+    assert_eq!(other.to_std(), "fe80::1234:5678:9abc:def0".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(addr.with_interface_id(0).to_std(), "fe80::".parse::<Ipv6Addr>().unwrap());
+}
+
+#[test]
+pub fn test_sockaddr_is_publicly_routable() {
+    let inet = |s: &str| SockAddr::new_inet(InetAddr::from_std(&s.parse().unwrap()));
+
+    assert!(inet("8.8.8.8:53").is_publicly_routable());
+    assert!(inet("[2606:4700::1111]:53").is_publicly_routable());
+
+    assert!(!inet("192.168.1.1:80").is_publicly_routable());
+    assert!(!inet("10.0.0.1:80").is_publicly_routable());
+    assert!(!inet("100.64.0.1:80").is_publicly_routable());
+    assert!(!inet("127.0.0.1:80").is_publicly_routable());
+    assert!(!inet("169.254.0.1:80").is_publicly_routable());
+    assert!(!inet("192.0.0.9:80").is_publicly_routable());
+    assert!(inet("192.0.1.1:80").is_publicly_routable());
+    assert!(!inet("192.0.2.1:80").is_publicly_routable());
+    assert!(!inet("198.19.0.1:80").is_publicly_routable());
+    assert!(!inet("224.0.0.1:80").is_publicly_routable());
+    assert!(!inet("255.255.255.255:80").is_publicly_routable());
+    assert!(!inet("[fd00::1]:80").is_publicly_routable());
+    assert!(!inet("[fe80::1]:80").is_publicly_routable());
+    assert!(!inet("[2001:db8::1]:80").is_publicly_routable());
+    assert!(!inet("[::1]:80").is_publicly_routable());
+    assert!(!SockAddr::new_unix("/tmp/s").unwrap().is_publicly_routable());
+}
+
+#[test]
+pub fn test_inetaddr_canonical_string() {
+    let inet = |s: &str| InetAddr::from_std(&s.parse().unwrap());
+
+    let a = inet("[2001:DB8::1]:80");
+    let b = inet("[2001:db8:0:0::1]:80");
+    assert_eq!(a.canonical_string(), "[2001:db8::1]:80");
+    assert_eq!(a.canonical_string(), b.canonical_string());
+    assert_eq!(inet("[2001:db8:0:0:1:0:0:1]:80").canonical_string(), "[2001:db8::1:0:0:1]:80");
+    assert_eq!(inet("[fe80::1%2]:22").canonical_string(), "[fe80::1%2]:22");
+    assert_eq!(inet("127.0.0.1:80").canonical_string(), "127.0.0.1:80");
+}
+
+#[test]
+pub fn test_ip_addr_is_adjacent() {
+    let a = NixIpv4Addr::new(10, 0, 0, 255);
+    let b = NixIpv4Addr::new(10, 0, 1, 0);
+    assert!(a.is_adjacent(&b));
+    assert!(b.is_adjacent(&a));
+    assert!(!a.is_adjacent(&a));
+    assert!(!a.is_adjacent(&NixIpv4Addr::new(10, 0, 1, 1)));
+    assert!(!NixIpv4Addr::new(255, 255, 255, 255).is_adjacent(&NixIpv4Addr::new(0, 0, 0, 0)));
+
+    let v6 = |s: &str| s.parse::<NixIpv6Addr>().unwrap();
+    assert!(v6("2001:db8::ffff").is_adjacent(&v6("2001:db8::1:0")));
+    assert!(!v6("2001:db8::1").is_adjacent(&v6("2001:db8::3")));
+    assert!(!v6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").is_adjacent(&v6("::")));
+}
+
+#[test]
+pub fn test_sockaddr_bytes_round_trip() {
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    fn check(addr: SockAddr) {
+        let bytes = addr.to_bytes();
+        match SockAddr::from_bytes(&bytes) {
+            Ok(ref back) if *back == addr => (),
+            _ => panic!("{} did not survive a round trip through {:?}", addr, bytes),
+        }
+    }
+
+    let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+    for _ in 0..1000 {
+        let v4 = IpAddr::V4(NixIpv4Addr::new(rng.gen(), rng.gen(), rng.gen(), rng.gen()));
+        check(SockAddr::new_inet(InetAddr::new(v4, rng.gen())));
+
+        let v6 = Ipv6Addr::new(rng.gen(), rng.gen(), rng.gen(), rng.gen(),
+                               rng.gen(), rng.gen(), rng.gen(), rng.gen());
+        let sa = SocketAddrV6::new(v6, rng.gen(), rng.gen::<u32>() & 0xfffff, rng.gen());
+        check(SockAddr::new_inet(InetAddr::from_std(&SocketAddr::V6(sa))));
+
+        let len = rng.gen_range(1, 100);
+        let path: Vec<u8> = (0..len).map(|_| rng.gen_range(1, 256) as u8).collect();
+        check(SockAddr::new_unix(&path[..]).unwrap());
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let len = rng.gen_range(0, MAX_ABSTRACT_LEN + 1);
+            let name: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            check(SockAddr::Unix(UnixAddr::new_abstract(&name).unwrap()));
+
+            check(SockAddr::new_netlink(rng.gen(), rng.gen()));
+            check(SockAddr::new_vsock(rng.gen(), rng.gen()));
+        }
+    }
+    check(SockAddr::Unix(UnixAddr::from_tagged_string("unix:").unwrap()));
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use nix::sys::socket::KeyAddr;
+        check(SockAddr::Key(KeyAddr::new()));
+    }
+
+    // There is no packet (AF_PACKET) variant of SockAddr yet, so packet
+    // addresses can't round-trip; make sure they are at least rejected
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use std::slice;
+
+        let mut ll: libc::sockaddr_ll = unsafe { mem::zeroed() };
+        ll.sll_family = libc::AF_PACKET as libc::c_ushort;
+        let bytes = unsafe {
+            slice::from_raw_parts(&ll as *const _ as *const u8, mem::size_of_val(&ll))
+        };
+        assert_eq!(SockAddr::from_bytes(bytes).err(), Some(Error::Sys(Errno::EAFNOSUPPORT)));
+    }
+
+    assert!(SockAddr::from_bytes(&[0; 1024]).is_err());
+}
+
+#[test]
+pub fn test_ip_addr_hex_string() {
+    let v4 = NixIpv4Addr::new(127, 0, 0, 1);
+    assert_eq!(v4.to_hex_string(), "7f000001");
+    assert!(NixIpv4Addr::from_hex_string("7f000001").unwrap() == v4);
+    assert!(NixIpv4Addr::from_hex_string("7F000001").unwrap() == v4);
+    assert!(NixIpv4Addr::from_hex_string("7f00001").is_err());
+    assert!(NixIpv4Addr::from_hex_string("+7f00001").is_err());
+    assert!(NixIpv4Addr::from_hex_string("7f00000g").is_err());
+
+    let v6: NixIpv6Addr = "2001:db8::1".parse().unwrap();
+    let hex = v6.to_hex_string();
+    assert_eq!(hex, "20010db8000000000000000000000001");
+    assert!(NixIpv6Addr::from_hex_string(&hex).unwrap() == v6.octets());
+    assert!(NixIpv6Addr::from_hex_string(&hex[1..]).is_err());
+    assert!(NixIpv6Addr::from_hex_string("2001:db8::1").is_err());
+}
+
+#[test]
+pub fn test_unixaddr_same_file() {
+    use std::os::unix::fs::symlink;
+    use std::os::unix::net::UnixListener;
+    use tempdir::TempDir;
+
+    let tempdir = TempDir::new("test_unixaddr_same_file").unwrap();
+    let real = tempdir.path().join("real");
+    let link = tempdir.path().join("link");
+    std::fs::create_dir(&real).unwrap();
+    symlink(&real, &link).unwrap();
+    let _listener = UnixListener::bind(real.join("s.sock")).unwrap();
+
+    let a = UnixAddr::new(&real.join("s.sock")).unwrap();
+    let b = UnixAddr::new(&link.join("s.sock")).unwrap();
+    assert!(a != b);
+    assert!(a.same_file(&b).unwrap());
+    assert!(b.same_file(&a).unwrap());
+
+    let missing = UnixAddr::new(&real.join("missing.sock")).unwrap();
+    assert!(a.same_file(&missing).is_err());
+
+    let unnamed = UnixAddr::from_tagged_string("unix:").unwrap();
+    assert!(!a.same_file(&unnamed).unwrap());
+}
+
+#[test]
+pub fn test_inetaddr_with_clamped_port() {
+    let inet = |s: &str| InetAddr::from_std(&s.parse().unwrap());
+
+    assert_eq!(inet("127.0.0.1:80").with_clamped_port(49152, 65535).unwrap().port(), 49152);
+    assert_eq!(inet("[::1]:50000").with_clamped_port(40000, 45000).unwrap().port(), 45000);
+    assert!(inet("127.0.0.1:50000").with_clamped_port(49152, 65535).unwrap() == inet("127.0.0.1:50000"));
+    assert!(inet("127.0.0.1:80").with_clamped_port(1, 1).unwrap().port() == 1);
+    assert!(inet("127.0.0.1:80").with_clamped_port(2, 1).is_err());
+}
+
+#[test]
+pub fn test_sockaddr_describe() {
+    let inet = |s: &str| SockAddr::new_inet(InetAddr::from_std(&s.parse().unwrap()));
+    assert_eq!(inet("127.0.0.1:80").describe(), "IPv4 127.0.0.1:80");
+    assert_eq!(inet("[::1]:80").describe(), "IPv6 [::1]:80");
+    assert_eq!(SockAddr::new_unix("/tmp/s").unwrap().describe(), "UNIX socket /tmp/s");
+    let unnamed = SockAddr::Unix(UnixAddr::from_tagged_string("unix:").unwrap());
+    assert_eq!(unnamed.describe(), "unnamed UNIX socket");
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let abs = SockAddr::Unix(UnixAddr::new_abstract(b"name").unwrap());
+        assert_eq!(abs.describe(), "UNIX socket @name");
+        assert_eq!(SockAddr::new_key().describe(), "AF_KEY socket pf_key");
+    }
+}
+
+#[test]
+pub fn test_ipv6addr_nat64_embedded_v4() {
+    let v6 = |s: &str| s.parse::<NixIpv6Addr>().unwrap();
+    assert!(v6("64:ff9b::1.2.3.4").nat64_embedded_v4() == Some(NixIpv4Addr::new(1, 2, 3, 4)));
+    assert!(v6("64:ff9b:1::1.2.3.4").nat64_embedded_v4().is_none());
+    assert!(v6("2001:db8::1.2.3.4").nat64_embedded_v4().is_none());
+}
+
+#[test]
+pub fn test_sockaddr_new_inet_scoped() {
+    let ip: NixIpv6Addr = "fe80::1".parse().unwrap();
+    let addr = SockAddr::new_inet_scoped(ip, 5353, 3);
+    assert_eq!(addr.to_str(), "[fe80::1%3]:5353");
+
+    let (sa, len) = unsafe { addr.as_ffi_pair() };
+    let back = unsafe { SockAddr::from_msghdr_name(sa, len) }.unwrap();
+    assert!(back == addr);
+    match back.ip_and_scope() {
+        Some((IpAddr::V6(ref v6), Some(3))) => assert!(*v6 == ip.octets()),
+        _ => panic!("scope id was lost"),
+    }
+
+    let v4 = SockAddr::new_inet(nix::sys::socket::InetAddr::from_std(&"127.0.0.1:80".parse().unwrap()));
+    match v4.ip_and_scope() {
+        Some((IpAddr::V4(..), None)) => (),
+        _ => panic!("unexpected scope for an IPv4 address"),
+    }
+    assert!(SockAddr::new_unix("/tmp/s").unwrap().ip_and_scope().is_none());
+}
+
+#[test]
+pub fn test_addressfamily_to_pf() {
+    assert_eq!(AddressFamily::Unix.to_pf(), libc::PF_UNIX);
+    assert_eq!(AddressFamily::Inet.to_pf(), libc::PF_INET);
+    assert_eq!(AddressFamily::Inet6.to_pf(), libc::PF_INET6);
+    for af in &[AddressFamily::Unix, AddressFamily::Inet, AddressFamily::Inet6] {
+        assert_eq!(af.to_pf(), *af as i32);
+    }
+}
+
+#[test]
+pub fn test_unixaddr_kind_predicates() {
+    let path = UnixAddr::new("/tmp/s").unwrap();
+    assert!(!path.is_abstract());
+    assert!(!path.is_unnamed());
+    assert!(path.path().is_some());
+
+    let unnamed = UnixAddr::from_tagged_string("unix:").unwrap();
+    assert!(!unnamed.is_abstract());
+    assert!(unnamed.is_unnamed());
+    assert!(unnamed.path().is_none());
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let abs = UnixAddr::new_abstract(b"name").unwrap();
+        assert!(abs.is_abstract());
+        assert!(!abs.is_unnamed());
+        assert!(abs.path().is_none());
+
+        // An empty abstract name is still abstract, not unnamed
+        let empty = UnixAddr::new_abstract(b"").unwrap();
+        assert!(empty.is_abstract());
+        assert!(!empty.is_unnamed());
+    }
+}
+
+#[cfg(feature = "smoltcp")]
+#[test]
+pub fn test_smoltcp_conversions() {
+    use smoltcp::wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv6Address};
+
+    let v4 = NixIpv4Addr::new(192, 0, 2, 1);
+    let smol: Ipv4Address = v4.into();
+    assert_eq!(smol, Ipv4Address::new(192, 0, 2, 1));
+    assert!(NixIpv4Addr::from(smol) == v4);
+
+    let v6: NixIpv6Addr = "2001:db8::1".parse().unwrap();
+    let smol: Ipv6Address = v6.into();
+    assert_eq!(smol.0, v6.octets());
+    assert!(NixIpv6Addr::from(smol) == v6.octets());
+
+    match IpAddress::from(IpAddr::V4(v4)) {
+        IpAddress::Ipv4(ip) => assert_eq!(ip, Ipv4Address::new(192, 0, 2, 1)),
+        _ => panic!("wrong IP version"),
+    }
+
+    for s in &["192.0.2.1:80", "[2001:db8::1]:443"] {
+        let inet = InetAddr::from_std(&s.parse().unwrap());
+        let endpoint: IpEndpoint = inet.into();
+        assert_eq!(endpoint.port, inet.port());
+        assert!(InetAddr::from(endpoint) == inet);
+    }
+}
+
+#[test]
+pub fn test_sockaddr_inet_parts() {
+    let addr = SockAddr::new_inet(InetAddr::from_std(&"192.0.2.1:8080".parse().unwrap()));
+    match addr.inet_parts() {
+        Some((IpAddr::V4(ip), port)) => {
+            assert_eq!(ip.octets(), [192, 0, 2, 1]);
+            assert_eq!(port, 8080);
+        }
+        _ => panic!("expected IPv4 parts"),
+    }
+    assert!(SockAddr::new_unix("/tmp/s").unwrap().inet_parts().is_none());
+}
+
+#[test]
+pub fn test_ip_addr_network_and_broadcast() {
+    let net = NixIpv4Addr::new(192, 168, 1, 0);
+    let bcast = NixIpv4Addr::new(192, 168, 1, 255);
+    let host = NixIpv4Addr::new(192, 168, 1, 7);
+    assert!(net.is_network_address(24));
+    assert!(!net.is_broadcast_address(24));
+    assert!(bcast.is_broadcast_address(24));
+    assert!(!bcast.is_network_address(24));
+    assert!(!host.is_network_address(24));
+    assert!(!host.is_broadcast_address(24));
+    assert!(!net.is_network_address(31));
+    assert!(!bcast.is_broadcast_address(32));
+    assert!(NixIpv4Addr::new(0, 0, 0, 0).is_network_address(0));
+
+    let v6 = |s: &str| s.parse::<NixIpv6Addr>().unwrap();
+    assert!(v6("2001:db8::").is_network_address(64));
+    assert!(!v6("2001:db8::1").is_network_address(64));
+    assert!(!v6("2001:db8::").is_network_address(128));
+}
+
+#[test]
+pub fn test_sockaddr_is_bindable_is_connectable() {
+    let inet = |s: &str| SockAddr::new_inet(InetAddr::from_std(&s.parse().unwrap()));
+
+    let wildcard = inet("0.0.0.0:80");
+    assert!(wildcard.is_bindable());
+    assert!(!wildcard.is_connectable());
+    assert!(inet("[::]:80").is_bindable());
+    assert!(!inet("[::]:80").is_connectable());
+
+    let loopback = inet("127.0.0.1:80");
+    assert!(loopback.is_bindable());
+    assert!(loopback.is_connectable());
+
+    assert!(inet("127.0.0.1:0").is_bindable());
+    assert!(!inet("127.0.0.1:0").is_connectable());
+    assert!(!inet("255.255.255.255:80").is_bindable());
+
+    let unix = SockAddr::new_unix("/tmp/s").unwrap();
+    assert!(unix.is_bindable() && unix.is_connectable());
+    let unnamed = SockAddr::Unix(UnixAddr::from_tagged_string("unix:").unwrap());
+    assert!(!unnamed.is_bindable() && !unnamed.is_connectable());
+}
+
+#[test]
+pub fn test_inetaddr_multicast_listener() {
+    let v4 = InetAddr::multicast_listener(5353, false);
+    assert!(v4.is_wildcard());
+    assert_eq!(v4.to_std(), "0.0.0.0:5353".parse().unwrap());
+
+    let v6 = InetAddr::multicast_listener(5353, true);
+    assert!(v6.is_wildcard());
+    assert_eq!(v6.to_std(), "[::]:5353".parse().unwrap());
+}
+
+#[test]
+pub fn test_ipv6addr_ul_bit() {
+    let addr = NixIpv6Addr::link_local_from_mac([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
+    assert!(addr.is_universal_iid());
+
+    let flipped = addr.with_flipped_ul_bit();
+    assert!(!flipped.is_universal_iid());
+    assert_eq!(flipped.to_std(), "fe80::11:22ff:fe33:4455".parse::<Ipv6Addr>().unwrap());
+    assert!(flipped.with_flipped_ul_bit() == addr.octets());
+}
+
+#[test]
+pub fn test_sockaddr_recv_buffer() {
+    let (storage, len) = SockAddr::recv_buffer(AddressFamily::Inet);
+    assert_eq!(storage.ss_family as i32, libc::AF_INET);
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_in>());
+
+    let (_, len) = SockAddr::recv_buffer(AddressFamily::Inet6);
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_in6>());
+
+    let (_, len) = SockAddr::recv_buffer(AddressFamily::Unix);
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_un>());
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let (storage, len) = SockAddr::recv_buffer(AddressFamily::Netlink);
+        assert_eq!(storage.ss_family as i32, libc::AF_NETLINK);
+        assert_eq!(len as usize, mem::size_of::<libc::sockaddr_nl>());
+    }
+}
+
+#[test]
+pub fn test_unixaddr_eq_kinds() {
+    fn raw(bytes: &[u8]) -> UnixAddr {
+        let mut sun: libc::sockaddr_un = unsafe { mem::zeroed() };
+        sun.sun_family = libc::AF_UNIX as libc::sa_family_t;
+        for (dst, &src) in sun.sun_path.iter_mut().zip(bytes) {
+            *dst = src as libc::c_char;
+        }
+        UnixAddr(sun, bytes.len())
+    }
+
+    let unnamed = raw(b"");
+    assert!(unnamed == UnixAddr::from_tagged_string("unix:").unwrap());
+    assert!(raw(b"foo") == UnixAddr::new("foo").unwrap());
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let abs = UnixAddr::new_abstract(b"foo").unwrap();
+        // A path can't start with a null, so these bytes are abstract too
+        assert!(raw(b"\0foo") == abs);
+        assert!(raw(b"foo") != abs);
+        // An empty abstract name is distinct from an unnamed address
+        assert!(UnixAddr::new_abstract(b"").unwrap() != unnamed);
+    }
+
+    let mut other_family = raw(b"foo");
+    other_family.0.sun_family = 0;
+    assert!(other_family != raw(b"foo"));
+}
+
+#[test]
+pub fn test_ipaddr_std_round_trip() {
+    for s in &["192.0.2.1", "2001:db8::1", "fe80::1"] {
+        let std: net::IpAddr = s.parse().unwrap();
+        assert_eq!(IpAddr::from_std(&std).to_std(), std);
+        assert_eq!(net::IpAddr::from(IpAddr::from(std)), std);
+    }
+
+    // The scope id lives in the socket address and survives a round trip
+    let std = SocketAddr::V6(SocketAddrV6::new("fe80::1".parse().unwrap(), 22, 0, 4));
+    let inet = InetAddr::from_std(&std);
+    assert_eq!(inet.to_std(), std);
+    assert_eq!(inet.ip().to_std(), std.ip());
+    match SockAddr::new_inet(inet).ip_and_scope() {
+        Some((ip, Some(4))) => assert_eq!(ip.to_std(), std.ip()),
+        _ => panic!("scope id was lost"),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_vsock_addr() {
+    let addr = SockAddr::new_vsock(libc::VMADDR_CID_ANY, 1234);
+    assert_eq!(addr.family(), AddressFamily::Vsock);
+    match addr {
+        SockAddr::Vsock(ref vsock) => {
+            assert_eq!(vsock.cid(), libc::VMADDR_CID_ANY);
+            assert_eq!(vsock.port(), 1234);
+        }
+        _ => panic!("expected a vsock address"),
+    }
+
+    let (sa, len) = unsafe { addr.as_ffi_pair() };
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_vm>());
+    assert_eq!(sa.sa_family as i32, libc::AF_VSOCK);
+    let back = unsafe { SockAddr::from_msghdr_name(sa, len) }.unwrap();
+    assert!(back == addr);
+    assert_eq!(back.to_str(), format!("cid: {} port: 1234", libc::VMADDR_CID_ANY));
+}
+
+#[test]
+pub fn test_flow_hash() {
+    use nix::sys::socket::flow_hash;
+    use std::net::SocketAddr;
+
+    let inet = |s: &str| InetAddr::from_std(&s.parse::<SocketAddr>().unwrap());
+    let a = inet("192.0.2.1:40000");
+    let b = inet("198.51.100.7:443");
+
+    assert_eq!(flow_hash(&a, &b), flow_hash(&a, &b));
+    assert_eq!(flow_hash(&a, &b), flow_hash(&inet("192.0.2.1:40000"), &b));
+    assert!(flow_hash(&a, &b) != flow_hash(&b, &a));
+    assert!(flow_hash(&a, &b) != flow_hash(&a, &inet("198.51.100.7:444")));
+
+    // Same bytes in a different family must not collide
+    let a6 = inet("[::c000:201]:40000");
+    let b6 = inet("[::c633:6407]:443");
+    assert!(flow_hash(&a, &b) != flow_hash(&a6, &b6));
+    assert!(flow_hash(&a, &b) != flow_hash(&a, &b6));
+}
+
+#[test]
+pub fn test_ipv6_same_subnet64() {
+    let a: NixIpv6Addr = "2001:db8:1:2::1".parse().unwrap();
+    let b: NixIpv6Addr = "2001:db8:1:2:ffff:ffff:ffff:ffff".parse().unwrap();
+    let c: NixIpv6Addr = "2001:db8:1:3::1".parse().unwrap();
+
+    assert!(a.same_subnet64(&a));
+    assert!(a.same_subnet64(&b));
+    assert!(b.same_subnet64(&a));
+    assert!(!a.same_subnet64(&c));
+    assert!(!c.same_subnet64(&b));
+}
+
+#[test]
+pub fn test_unixaddr_kinds_from_msghdr_name() {
+    fn round_trip(addr: &UnixAddr) -> UnixAddr {
+        let addr = SockAddr::Unix(*addr);
+        let (sa, len) = unsafe { addr.as_ffi_pair() };
+        match unsafe { SockAddr::from_msghdr_name(sa, len) } {
+            Some(SockAddr::Unix(unix)) => unix,
+            _ => panic!("expected a unix address"),
+        }
+    }
+
+    let unnamed = round_trip(&UnixAddr::from_tagged_string("unix:").unwrap());
+    assert!(unnamed.is_unnamed());
+    assert_eq!(unnamed.path(), None);
+    assert_eq!(unnamed.as_abstract(), None);
+
+    let path = round_trip(&UnixAddr::new("/tmp/peer.sock").unwrap());
+    assert_eq!(path.path(), Some(Path::new("/tmp/peer.sock")));
+    assert_eq!(path.as_abstract(), None);
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let abs = round_trip(&UnixAddr::new_abstract(b"peer").unwrap());
+        assert_eq!(abs.path(), None);
+        assert_eq!(abs.as_abstract(), Some(&b"peer"[..]));
+        let empty = round_trip(&UnixAddr::new_abstract(b"").unwrap());
+        assert_eq!(empty.as_abstract(), Some(&b""[..]));
+        assert!(!empty.is_unnamed());
+    }
+}
+
+#[test]
+pub fn test_sockaddr_into_owned() {
+    let inet = SockAddr::new_inet(InetAddr::from_std(&"[::1]:9000".parse().unwrap()));
+    let unix = SockAddr::new_unix("/tmp/owned.sock").unwrap();
+    for addr in &[inet, unix] {
+        assert!(addr.into_owned() == *addr);
+    }
+}
+
+#[test]
+pub fn test_inetaddr_sockaddr_from_str() {
+    use std::iter;
+
+    for s in &["127.0.0.1:8080", "[::1]:9000", "[2001:db8::1]:0", "[fe80::1%2]:80"] {
+        let inet = s.parse::<InetAddr>().unwrap();
+        assert_eq!(inet.to_str(), *s);
+        let addr = s.parse::<SockAddr>().unwrap();
+        assert!(addr == SockAddr::Inet(inet));
+        assert_eq!(addr.to_str(), *s);
+    }
+
+    assert!("127.0.0.1".parse::<InetAddr>().is_err());
+    assert!("::1".parse::<InetAddr>().is_err());
+    assert!("[fe80::1%eth0]:80".parse::<InetAddr>().is_err());
+    match "[fe80::1%2]:80".parse::<InetAddr>().unwrap() {
+        InetAddr::V6(sa) => assert_eq!(sa.sin6_scope_id, 2),
+        _ => panic!("expected an IPv6 address"),
+    }
+
+    let path = "/tmp/foo.sock".parse::<SockAddr>().unwrap();
+    assert!(path == SockAddr::new_unix("/tmp/foo.sock").unwrap());
+    assert_eq!(path.to_str(), "/tmp/foo.sock");
+    assert_eq!(path.to_str().parse::<SockAddr>().unwrap().to_str(), "/tmp/foo.sock");
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let abs = "@name".parse::<SockAddr>().unwrap();
+        assert!(abs == SockAddr::Unix(UnixAddr::new_abstract(b"name").unwrap()));
+        assert_eq!(abs.to_str(), "@name");
+    }
+
+    let long: String = iter::once('/').chain(iter::repeat('x').take(200)).collect();
+    assert_eq!(long.parse::<SockAddr>().err(), Some(Error::Sys(Errno::ENAMETOOLONG)));
+    assert!("".parse::<SockAddr>().is_err());
+}
+
+#[test]
+pub fn test_reverse_dns_labels() {
+    let v4 = NixIpv4Addr::new(1, 2, 3, 4);
+    assert_eq!(v4.reverse_dns_labels(), ["4", "3", "2", "1", "in-addr", "arpa"]);
+
+    let v6: NixIpv6Addr = "2001:db8::abcd".parse().unwrap();
+    let labels = v6.reverse_dns_labels();
+    assert_eq!(labels.len(), 34);
+    assert_eq!(&labels[..4], ["d", "c", "b", "a"]);
+    assert!(labels[4..24].iter().all(|l| l == "0"));
+    assert_eq!(&labels[24..], ["8", "b", "d", "0", "1", "0", "0", "2", "ip6", "arpa"]);
+}
+
+#[test]
+pub fn test_address_family_i32_round_trip() {
+    let mut families = vec![AddressFamily::Unix, AddressFamily::Inet, AddressFamily::Inet6];
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    families.extend_from_slice(&[AddressFamily::Netlink, AddressFamily::Key,
+                                 AddressFamily::Vsock, AddressFamily::Packet]);
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    families.push(AddressFamily::Xdp);
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    families.push(AddressFamily::System);
+    #[cfg(any(target_os = "macos",
+              target_os = "ios",
+              target_os = "freebsd",
+              target_os = "dragonfly",
+              target_os = "openbsd",
+              target_os = "netbsd"))]
+    families.push(AddressFamily::Link);
+
+    for af in families {
+        assert_eq!(AddressFamily::from_i32(af.to_i32()), Some(af));
+    }
+    assert_eq!(AddressFamily::Inet.to_i32(), libc::AF_INET);
+    assert_eq!(AddressFamily::from_i32(-1), None);
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+pub fn test_unixaddr_pair_in_tempdir() {
+    use nix::sys::socket::{bind, socket, SockFlag, SockType};
+    use nix::unistd::close;
+
+    let (a, guard_a) = UnixAddr::pair_in_tempdir().unwrap();
+    let (b, guard_b) = UnixAddr::pair_in_tempdir().unwrap();
+    assert!(a != b);
+    assert!(a.path().unwrap().starts_with(guard_a.path()));
+    assert!(guard_a.path() != guard_b.path());
+
+    let fd = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), 0).unwrap();
+    bind(fd, &SockAddr::Unix(a)).unwrap();
+    close(fd).unwrap();
+    assert!(a.path().unwrap().exists());
+
+    let dir = guard_a.path().to_owned();
+    drop(guard_a);
+    assert!(!dir.exists());
+}
+
+#[test]
+pub fn test_ip_eq_hash_and_classification() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+
+    fn hash_of<T: Hash>(t: &T) -> u64 {
+        let mut s = DefaultHasher::new();
+        t.hash(&mut s);
+        s.finish()
+    }
+
+    let v6 = |s: &str| s.parse::<NixIpv6Addr>().unwrap();
+    let loopback = v6("::1");
+    let unspecified = v6("::");
+    let multicast = v6("ff02::1");
+    let global = [v6("2001:db8::1"), v6("2606:4700::1111")];
+
+    assert!(loopback.is_loopback() && !loopback.is_unspecified() && !loopback.is_multicast());
+    assert!(unspecified.is_unspecified() && !unspecified.is_loopback() && !unspecified.is_multicast());
+    assert!(multicast.is_multicast() && !multicast.is_loopback() && !multicast.is_unspecified());
+    for g in &global {
+        assert!(!g.is_loopback() && !g.is_unspecified() && !g.is_multicast());
+    }
+
+    assert!(v6("2001:db8::1") == global[0]);
+    assert!(global[0] != global[1]);
+    assert_eq!(hash_of(&v6("2001:db8::1")), hash_of(&global[0]));
+    let set: HashSet<NixIpv6Addr> = vec![loopback, v6("0::1"), unspecified].into_iter().collect();
+    assert_eq!(set.len(), 2);
+
+    assert!(NixIpv4Addr::new(127, 1, 2, 3).is_loopback());
+    assert!(NixIpv4Addr::any().is_unspecified());
+    assert!(NixIpv4Addr::new(224, 0, 0, 1).is_multicast());
+    assert!(NixIpv4Addr::new(239, 255, 255, 255).is_multicast());
+    let public = NixIpv4Addr::new(8, 8, 8, 8);
+    assert!(!public.is_loopback() && !public.is_unspecified() && !public.is_multicast());
+}
+
+#[test]
+pub fn test_inetaddr_format_with() {
+    let v4: InetAddr = "192.0.2.1:80".parse().unwrap();
+    let v6: InetAddr = "[2001:db8::1]:443".parse().unwrap();
+
+    assert_eq!(v4.format_with(|_| "example.com".to_owned()), "example.com:80");
+    assert_eq!(v6.format_with(|_| "example.com".to_owned()), "[example.com]:443");
+    assert_eq!(v4.format_with(|ip| ip.to_string()), v4.to_str());
+    assert_eq!(v6.format_with(|ip| ip.to_string()), v6.to_str());
+}
+
+#[test]
+pub fn test_sockaddr_from_libc_sockaddr_exact() {
+    use std::ptr;
+
+    let v4 = SockAddr::new_inet("192.0.2.1:80".parse().unwrap());
+    let v6 = SockAddr::new_inet("[2001:db8::1]:443".parse().unwrap());
+    let unix = SockAddr::new_unix("/tmp/exact.sock").unwrap();
+
+    unsafe {
+        for addr in &[v4, v6, unix] {
+            let (sa, len) = addr.as_ffi_pair();
+            assert!(SockAddr::from_libc_sockaddr_exact(sa, len).unwrap() == *addr);
+        }
+
+        // A sockaddr_storage leaves room for trailing bytes
+        let mut storage: libc::sockaddr_storage = mem::zeroed();
+        let (sa, len) = v4.as_ffi_pair();
+        ptr::copy_nonoverlapping(sa as *const _ as *const u8,
+                                 &mut storage as *mut _ as *mut u8,
+                                 len as usize);
+        let sa = &storage as *const _ as *const libc::sockaddr;
+        let einval = Some(Error::Sys(Errno::EINVAL));
+        assert_eq!(SockAddr::from_libc_sockaddr_exact(sa, len - 1).err(), einval);
+        assert_eq!(SockAddr::from_libc_sockaddr_exact(sa, len + 4).err(), einval);
+        assert_eq!(SockAddr::from_libc_sockaddr_exact(sa, 0).err(), einval);
+        assert_eq!(SockAddr::from_libc_sockaddr_exact(ptr::null(), len).err(), einval);
+
+        // Unix addresses are variable length, up to a full sockaddr_un
+        let (sa, len) = unix.as_ffi_pair();
+        assert!(SockAddr::from_libc_sockaddr_exact(sa, len - 1).is_ok());
+        let full = mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+        assert!(SockAddr::from_libc_sockaddr_exact(sa, full).is_ok());
+        let mut storage: libc::sockaddr_storage = mem::zeroed();
+        storage.ss_family = libc::AF_UNIX as libc::sa_family_t;
+        let sa = &storage as *const _ as *const libc::sockaddr;
+        assert!(SockAddr::from_libc_sockaddr_exact(sa, full + 1).is_err());
+    }
+}
+
+#[test]
+pub fn test_ipv4addr_classful_netmask() {
+    let mask = |a, b, c, d| Some(NixIpv4Addr::new(a, b, c, d));
+    assert!(NixIpv4Addr::new(10, 1, 2, 3).classful_netmask() == mask(255, 0, 0, 0));
+    assert!(NixIpv4Addr::new(127, 255, 255, 255).classful_netmask() == mask(255, 0, 0, 0));
+    assert!(NixIpv4Addr::new(128, 0, 0, 1).classful_netmask() == mask(255, 255, 0, 0));
+    assert!(NixIpv4Addr::new(172, 16, 0, 1).classful_netmask() == mask(255, 255, 0, 0));
+    assert!(NixIpv4Addr::new(192, 168, 1, 1).classful_netmask() == mask(255, 255, 255, 0));
+    assert!(NixIpv4Addr::new(223, 255, 255, 1).classful_netmask() == mask(255, 255, 255, 0));
+    assert!(NixIpv4Addr::new(224, 0, 0, 1).classful_netmask() == None);
+    assert!(NixIpv4Addr::new(240, 0, 0, 1).classful_netmask() == None);
+    assert!(NixIpv4Addr::new(255, 255, 255, 255).classful_netmask() == None);
+}
+
+#[test]
+pub fn test_sockaddr_sort_key() {
+    use std::cmp::Ordering;
+
+    let inet = |s: &str| SockAddr::new_inet(s.parse().unwrap());
+    let mut addrs = vec![
+        inet("[::1]:80"),
+        SockAddr::new_unix("/tmp/b.sock").unwrap(),
+        inet("10.0.0.1:443"),
+        inet("[2001:db8::1]:22"),
+        inet("10.0.0.1:80"),
+        SockAddr::new_unix("/tmp/a.sock").unwrap(),
+        inet("9.255.255.255:65535"),
+        inet("[::1]:8080"),
+    ];
+    let mut by_ord = addrs.clone();
+    addrs.sort_by_key(|a| a.sort_key());
+    by_ord.sort();
+
+    // IPv4, then IPv6, then Unix; within a family by address, then port
+    let expected = vec![
+        inet("9.255.255.255:65535"),
+        inet("10.0.0.1:80"),
+        inet("10.0.0.1:443"),
+        inet("[::1]:80"),
+        inet("[::1]:8080"),
+        inet("[2001:db8::1]:22"),
+        SockAddr::new_unix("/tmp/a.sock").unwrap(),
+        SockAddr::new_unix("/tmp/b.sock").unwrap(),
+    ];
+    assert_eq!(addrs.len(), expected.len());
+    for ((a, o), e) in addrs.iter().zip(by_ord.iter()).zip(expected.iter()) {
+        assert!(a == e, "{} != {}", a, e);
+        assert!(o == e, "{} != {}", o, e);
+    }
+    assert!(inet("[::1]:80") < inet("[::1]:8080"));
+    assert!(inet("10.0.0.1:80").cmp(&inet("10.0.0.1:80")) == Ordering::Equal);
+
+    // Ord agrees with == on addresses that differ only in fields that don't
+    // show in the address text
+    let unix = |s: &str| SockAddr::new_unix(s).unwrap();
+    let mut other_family = UnixAddr::new("foo").unwrap();
+    other_family.0.sun_family = 0;
+    let mut edge_cases = Vec::new();
+    edge_cases.extend_from_slice(&[
+        unix("foo"),
+        unix("foo"),
+        SockAddr::Unix(other_family),
+        SockAddr::Unix(UnixAddr::from_tagged_string("unix:").unwrap()),
+        inet("[fe80::1%1]:80"),
+        inet("[fe80::1%2]:80"),
+        SockAddr::new_inet(InetAddr::from_std(&SocketAddr::V6(
+            SocketAddrV6::new("fe80::1".parse().unwrap(), 80, 7, 1)))),
+    ]);
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        edge_cases.push(SockAddr::Unix(UnixAddr::new_abstract(b"foo").unwrap()));
+        edge_cases.push(SockAddr::Unix(UnixAddr::new_abstract(b"").unwrap()));
+    }
+    for a in &edge_cases {
+        for b in &edge_cases {
+            assert_eq!(a.cmp(b) == Ordering::Equal, a == b, "{} vs {}", a, b);
+        }
+    }
+}
+
+#[test]
+pub fn test_inetaddr_v6_parts() {
+    let std = SocketAddrV6::new("fe80::1".parse().unwrap(), 22, 0x12345, 3);
+    let inet = InetAddr::from_std(&SocketAddr::V6(std));
+    let (ip, flowinfo, scope_id) = inet.v6_parts().unwrap();
+    assert!(ip.to_std() == *std.ip());
+    assert_eq!(flowinfo, 0x12345);
+    assert_eq!(scope_id, 3);
+
+    let unscoped: InetAddr = "[2001:db8::1]:80".parse().unwrap();
+    assert_eq!(unscoped.v6_parts().map(|(_, f, s)| (f, s)), Some((0, 0)));
+
+    let v4: InetAddr = "127.0.0.1:80".parse().unwrap();
+    assert!(v4.v6_parts().is_none());
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_unixaddr_abstract_from_path_like() {
+    use std::iter;
+
+    let addr = UnixAddr::abstract_from_path_like("myapp/worker/control").unwrap();
+    assert_eq!(addr.as_abstract(), Some(&b"myapp/worker/control"[..]));
+    assert!(addr != UnixAddr::abstract_from_path_like("myapp/worker/contro").unwrap());
+
+    let longest: String = iter::repeat('x').take(MAX_ABSTRACT_LEN).collect();
+    assert!(UnixAddr::abstract_from_path_like(&longest).is_ok());
+    let too_long = format!("{}y", longest);
+    assert_eq!(UnixAddr::abstract_from_path_like(&too_long).err(),
+               Some(Error::Sys(Errno::ENAMETOOLONG)));
+
+    assert!(UnixAddr::abstract_from_path_like("").is_err());
+    assert!(UnixAddr::abstract_from_path_like("a\0b").is_err());
+}
+
+#[test]
+pub fn test_inetaddr_same_link() {
+    let inet = |s: &str| s.parse::<InetAddr>().unwrap();
+    let a = inet("192.168.1.10:80");
+
+    assert!(a.same_link(&inet("192.168.1.200:443"), 24));
+    assert!(!a.same_link(&inet("192.168.2.10:80"), 24));
+    assert!(a.same_link(&inet("192.168.2.10:80"), 16));
+    assert!(a.same_link(&inet("10.0.0.1:80"), 0));
+    assert!(!a.same_link(&inet("192.168.1.11:80"), 40));
+
+    assert!(inet("[2001:db8::1]:80").same_link(&inet("[2001:db8::ffff]:80"), 64));
+    assert!(!inet("[2001:db8::1]:80").same_link(&inet("[2001:db8:0:1::1]:80"), 64));
+    assert!(!a.same_link(&inet("[::ffff:192.168.1.10]:80"), 0));
+}
+
+#[test]
+pub fn test_sockaddr_serialized_size() {
+    let mut addrs = vec![
+        SockAddr::new_inet("127.0.0.1:80".parse().unwrap()),
+        SockAddr::new_inet("[::1]:80".parse().unwrap()),
+        SockAddr::new_unix("/tmp/size.sock").unwrap(),
+        SockAddr::Unix(UnixAddr::from_tagged_string("unix:").unwrap()),
+    ];
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    addrs.extend_from_slice(&[
+        SockAddr::Unix(UnixAddr::new_abstract(b"size").unwrap()),
+        SockAddr::new_netlink(0, 0),
+        SockAddr::new_key(),
+        SockAddr::new_vsock(libc::VMADDR_CID_HOST, 1024),
+    ]);
+
+    for addr in &addrs {
+        assert_eq!(addr.to_bytes().len(), addr.serialized_size(), "{}", addr);
+    }
+}
+
+#[cfg(feature = "ipnet")]
+#[test]
+pub fn test_ipnet_conversions() {
+    use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+    use nix::sys::socket::{Cidr, CidrV4, CidrV6};
+
+    let v4 = NixIpv4Addr::new(192, 168, 1, 10);
+    let std_v4: net::Ipv4Addr = v4.into();
+    assert_eq!(std_v4, net::Ipv4Addr::new(192, 168, 1, 10));
+    assert!(NixIpv4Addr::from(std_v4) == v4);
+
+    let v6: NixIpv6Addr = "2001:db8::1".parse().unwrap();
+    let std_v6: net::Ipv6Addr = v6.into();
+    assert_eq!(std_v6, "2001:db8::1".parse::<Ipv6Addr>().unwrap());
+    assert!(NixIpv6Addr::from(std_v6) == v6);
+
+    let net4 = v4.to_ipv4_net(24).unwrap();
+    assert_eq!(net4, "192.168.1.10/24".parse::<Ipv4Net>().unwrap());
+    let cidr4 = CidrV4::from_ipv4_net(&net4).unwrap();
+    assert!(cidr4.addr() == v4);
+    assert_eq!(cidr4.prefix_len(), 24);
+    assert_eq!(cidr4.to_ipv4_net().unwrap(), net4);
+    assert!(v4.to_ipv4_net(33).is_err());
+
+    let net6 = v6.to_ipv6_net(64).unwrap();
+    assert_eq!(net6, "2001:db8::1/64".parse::<Ipv6Net>().unwrap());
+    assert_eq!(CidrV6::from_ipv6_net(&net6).unwrap().to_ipv6_net().unwrap(), net6);
+    assert!(v6.to_ipv6_net(129).is_err());
+
+    let net = IpAddr::V6(v6).to_ip_net(48).unwrap();
+    assert_eq!(net, "2001:db8::1/48".parse::<IpNet>().unwrap());
+    assert_eq!(Cidr::from_ip_net(&net).unwrap().to_ip_net().unwrap(), net);
+    assert!(IpAddr::V4(v4).to_ip_net(33).is_err());
+}
+
+#[test]
+pub fn test_sockaddr_validate() {
+    let einval = Err(Error::Sys(Errno::EINVAL));
+
+    let mut v4: InetAddr = "192.0.2.1:80".parse().unwrap();
+    let mut v6: InetAddr = "[2001:db8::1]:443".parse().unwrap();
+    let mut unix = UnixAddr::new("/tmp/validate.sock").unwrap();
+    assert_eq!(SockAddr::Inet(v4).validate(), Ok(()));
+    assert_eq!(SockAddr::Inet(v6).validate(), Ok(()));
+    assert_eq!(SockAddr::Unix(unix).validate(), Ok(()));
+
+    if let InetAddr::V4(ref mut sa) = v4 {
+        sa.sin_family = libc::AF_INET6 as libc::sa_family_t;
+    }
+    assert_eq!(SockAddr::Inet(v4).validate(), einval);
+    if let InetAddr::V6(ref mut sa) = v6 {
+        sa.sin6_family = 0;
+    }
+    assert_eq!(SockAddr::Inet(v6).validate(), einval);
+
+    let mut too_long = unix;
+    too_long.1 = mem::size_of_val(&too_long.0.sun_path) + 1;
+    assert_eq!(SockAddr::Unix(too_long).validate(), einval);
+    unix.0.sun_family = libc::AF_INET as libc::sa_family_t;
+    assert_eq!(SockAddr::Unix(unix).validate(), einval);
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let mut vsock = SockAddr::new_vsock(libc::VMADDR_CID_HOST, 1024);
+        assert_eq!(vsock.validate(), Ok(()));
+        if let SockAddr::Vsock(ref mut addr) = vsock {
+            addr.0.svm_family = libc::AF_UNIX as libc::sa_family_t;
+        }
+        assert_eq!(vsock.validate(), einval);
+    }
+}
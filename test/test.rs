@@ -4,6 +4,10 @@ extern crate nix;
 extern crate lazy_static;
 extern crate libc;
 extern crate rand;
+#[cfg(feature = "smoltcp")]
+extern crate smoltcp;
+#[cfg(feature = "ipnet")]
+extern crate ipnet;
 extern crate tempdir;
 extern crate tempfile;
 